@@ -28,7 +28,7 @@ export function withdraw(symbol_ptr: i32, amount_ptr: i32): void {
   assert(user_balance >= amount_int, "insufficent funds")
 
   sdk.kv_increment(userVaultKey(symbol), -amount_int);
-  sdk.call("Coin", "transfer", [sdk.account_caller(), b(amount), b(symbol)])
+  sdk.transfer(sdk.account_caller(), amount, symbol)
 
   sdk.ret(`${user_balance - amount_int}`);
 }
@@ -4,7 +4,10 @@ import { b, bcat, b58 } from "./sdk";
 export function init(): void {
   //Create a collection
   //Soulbound = false
-  sdk.call("Nft", "create_collection", [b("AGENTIC"), b("false")])
+  sdk.call("Nft", "create_collection", [b("AGENTIC"), b("false"), b("Agentic"), b("https://ipfs.io/ipfs/bafybeicn7i3soqdgr7dwnrwytgq4zxy7a5jpkizrvhm5mv6bgjd32wm3q4/")])
+  //Per token uri overrides, readable on chain through Nft.view_token
+  sdk.call("Nft", "set_token_metadata", [b("AGENTIC"), b("2"), b("https://ipfs.io/ipfs/QmWBaeu6y1zEcKbsEqCuhuDHPL3W8pZouCPdafMCRCSUWk"), b("")])
+  sdk.call("Nft", "set_token_metadata", [b("AGENTIC"), b("6"), b("https://ipfs.io/ipfs/bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"), b("")])
 }
 
 export function view_nft(collection_ptr: i32, token_ptr: i32): void {
@@ -35,6 +38,5 @@ export function claim(): void {
 
 //Coming Soon: VRF Stage 2 will be only attackable if 67% are malicious as it will use BLS Threshold /w DKG
 export function roll_dice(): i64 {
-  const val = Math.random(); // Returns 0.0 to 1.0
-  return floor(val * 6) as i32 + 1; // Returns 1-6
+  return (sdk.random_u64() % 6) as i64 + 1; // Returns 1-6
 }
@@ -75,6 +75,5 @@ export function fight(): void {
 }
 
 export function roll_dice(sides: u32): i64 {
-  const val = Math.random(); // Returns 0.0 to 1.0
-  return (floor(val * sides) as i32) + 1; // Returns 1-sides
+  return (sdk.random_u64() % sides) as i64 + 1; // Returns 1-sides
 }
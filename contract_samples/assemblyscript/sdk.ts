@@ -202,30 +202,65 @@ export function exit(error: string): void {
   abort(error, "0", 0, 0);
 }
 
-// --- Seed (1100) ---
-export function seed(): Uint8Array { return memory_read_bytes(1100); }
-
-// --- Entry (2000) ---
-export function entry_slot(): u64 { return load<u64>(2000); }
-export function entry_height(): u64 { return load<u64>(2010); }
-export function entry_epoch(): u64 { return load<u64>(2020); }
-export function entry_signer(): Uint8Array { return memory_read_bytes(2100); }
-export function entry_prev_hash(): Uint8Array { return memory_read_bytes(2200); }
-export function entry_vr(): Uint8Array { return memory_read_bytes(2300); }
-export function entry_dr(): Uint8Array { return memory_read_bytes(2400); }
-
-// --- TX (3000) ---
-export function tx_nonce(): u64 { return load<u64>(3000); }
-export function tx_signer(): Uint8Array { return memory_read_bytes(3100); }
-
-// --- Accounts (4000) ---
-export function account_current(): Uint8Array { return memory_read_bytes(4000); }
-export function account_caller(): Uint8Array { return memory_read_bytes(4100); }
-export function account_origin(): Uint8Array { return memory_read_bytes(4200); }
-
-// --- Assets (5000) ---
-export function attached_symbol(): string { return memory_read_string(5000); }
-export function attached_amount(): string { return memory_read_string(5100); }
+// --- Memory layout shared with the host, [0, LAYOUT_RESERVED_END) is written by the host ---
+export const LAYOUT_VERSION: u32 = 1;
+export const LAYOUT_RESERVED_END: i32 = 65536;
+export const LAYOUT_VERSION_PTR: i32 = 1024;
+export const LAYOUT_SEED: i32 = 1100;
+export const LAYOUT_ENTRY_SLOT: i32 = 2000;
+export const LAYOUT_ENTRY_HEIGHT: i32 = 2010;
+export const LAYOUT_ENTRY_EPOCH: i32 = 2020;
+export const LAYOUT_ENTRY_SIGNER: i32 = 2100;
+export const LAYOUT_ENTRY_PREV_HASH: i32 = 2200;
+export const LAYOUT_ENTRY_VR: i32 = 2300;
+export const LAYOUT_ENTRY_DR: i32 = 2400;
+export const LAYOUT_TX_NONCE: i32 = 3000;
+export const LAYOUT_TX_SIGNER: i32 = 3100;
+export const LAYOUT_ACCOUNT_CURRENT: i32 = 4000;
+export const LAYOUT_ACCOUNT_CALLER: i32 = 4100;
+export const LAYOUT_ACCOUNT_ORIGIN: i32 = 4200;
+export const LAYOUT_ATTACHED_SYMBOL: i32 = 5000;
+export const LAYOUT_ATTACHED_AMOUNT: i32 = 5100;
+export const LAYOUT_IO: i32 = 10000;
+
+export function layout_version(): u32 { return load<u32>(<usize>LAYOUT_VERSION_PTR); }
+
+// --- Seed ---
+export function seed(): Uint8Array { return memory_read_bytes(LAYOUT_SEED); }
+
+// xorshift over the call seed, floats (Math.random) are rejected at deploy
+let rng_state: u64 = 0;
+export function random_u64(): u64 {
+  if (rng_state == 0) rng_state = load<u64>(<usize>(LAYOUT_SEED + 4)) | 1;
+  let x = rng_state;
+  x ^= x << 13;
+  x ^= x >> 7;
+  x ^= x << 17;
+  rng_state = x;
+  return x;
+}
+
+// --- Entry ---
+export function entry_slot(): u64 { return load<u64>(<usize>LAYOUT_ENTRY_SLOT); }
+export function entry_height(): u64 { return load<u64>(<usize>LAYOUT_ENTRY_HEIGHT); }
+export function entry_epoch(): u64 { return load<u64>(<usize>LAYOUT_ENTRY_EPOCH); }
+export function entry_signer(): Uint8Array { return memory_read_bytes(LAYOUT_ENTRY_SIGNER); }
+export function entry_prev_hash(): Uint8Array { return memory_read_bytes(LAYOUT_ENTRY_PREV_HASH); }
+export function entry_vr(): Uint8Array { return memory_read_bytes(LAYOUT_ENTRY_VR); }
+export function entry_dr(): Uint8Array { return memory_read_bytes(LAYOUT_ENTRY_DR); }
+
+// --- TX ---
+export function tx_nonce(): u64 { return load<u64>(<usize>LAYOUT_TX_NONCE); }
+export function tx_signer(): Uint8Array { return memory_read_bytes(LAYOUT_TX_SIGNER); }
+
+// --- Accounts ---
+export function account_current(): Uint8Array { return memory_read_bytes(LAYOUT_ACCOUNT_CURRENT); }
+export function account_caller(): Uint8Array { return memory_read_bytes(LAYOUT_ACCOUNT_CALLER); }
+export function account_origin(): Uint8Array { return memory_read_bytes(LAYOUT_ACCOUNT_ORIGIN); }
+
+// --- Assets ---
+export function attached_symbol(): string { return memory_read_string(LAYOUT_ATTACHED_SYMBOL); }
+export function attached_amount(): string { return memory_read_string(LAYOUT_ATTACHED_AMOUNT); }
 
 @external("env", "import_log")
 declare function import_log(ptr: i32, len: i32): void;
@@ -324,7 +359,123 @@ export function kv_get_next<K, V>(prefix: K, key: V): KeyValuePair {
   return new KeyValuePair(prev_key, value);
 }
 
+@external("env", "import_kv_scan")
+declare function import_kv_scan(prefix_ptr: i32, prefix_len: i32, start_ptr: i32, start_len: i32, end_ptr: i32, end_len: i32, limit: i32, reverse: i32): i32;
+// Returns up to `limit` pairs in [start, end) under prefix, keys without the prefix
+// Results are capped by the host, continue from the last key if more are expected
+export function kv_scan<K, S, E>(prefix: K, start: S, end: E, limit: i32, reverse: bool = false): KeyValuePair[] {
+  const prefixBytes = toBytes<K>(prefix);
+  const startBytes = toBytes<S>(start);
+  const endBytes = toBytes<E>(end);
+
+  const termPtr = import_kv_scan(changetype<i32>(prefixBytes.dataStart), prefixBytes.byteLength,
+    changetype<i32>(startBytes.dataStart), startBytes.byteLength,
+    changetype<i32>(endBytes.dataStart), endBytes.byteLength,
+    limit, reverse ? 1 : 0);
+  const count = load<i32>(termPtr);
+
+  const pairs = new Array<KeyValuePair>(count);
+  let offset = termPtr + 4;
+  for (let i = 0; i < count; i++) {
+    const key = memory_read_bytes(offset);
+    offset += 4 + key.byteLength;
+    const value = memory_read_bytes(offset);
+    offset += 4 + value.byteLength;
+    pairs[i] = new KeyValuePair(key, value);
+  }
+  return pairs;
+}
+
 // One import to rule them all
+@external("env", "import_coin_balance")
+declare function import_coin_balance(address_ptr: i32, address_len: i32, symbol_ptr: i32, symbol_len: i32): i32;
+export function coin_balance<A, S>(address: A, symbol: S): string {
+  const addressBytes = toBytes<A>(address);
+  const symbolBytes = toBytes<S>(symbol);
+  const rptr = import_coin_balance(changetype<i32>(addressBytes.dataStart), addressBytes.byteLength, changetype<i32>(symbolBytes.dataStart), symbolBytes.byteLength);
+  return memory_read_string(rptr);
+}
+
+@external("env", "import_coin_total_supply")
+declare function import_coin_total_supply(symbol_ptr: i32, symbol_len: i32): i32;
+export function coin_total_supply<S>(symbol: S): string {
+  const symbolBytes = toBytes<S>(symbol);
+  const rptr = import_coin_total_supply(changetype<i32>(symbolBytes.dataStart), symbolBytes.byteLength);
+  return memory_read_string(rptr);
+}
+
+@external("env", "import_nft_balance")
+declare function import_nft_balance(address_ptr: i32, address_len: i32, collection_ptr: i32, collection_len: i32, token_ptr: i32, token_len: i32): i32;
+export function nft_balance<A, C, T>(address: A, collection: C, token: T): string {
+  const addressBytes = toBytes<A>(address);
+  const collectionBytes = toBytes<C>(collection);
+  const tokenBytes = toBytes<T>(token);
+  const rptr = import_nft_balance(
+    changetype<i32>(addressBytes.dataStart), addressBytes.byteLength,
+    changetype<i32>(collectionBytes.dataStart), collectionBytes.byteLength,
+    changetype<i32>(tokenBytes.dataStart), tokenBytes.byteLength
+  );
+  return memory_read_string(rptr);
+}
+
+@external("env", "import_nft_royalty")
+declare function import_nft_royalty(collection_ptr: i32, collection_len: i32, price_ptr: i32, price_len: i32): i32;
+// Royalty owed on a sale at price, null when the collection has none
+export function nft_royalty<C, P>(collection: C, price: P): NftRoyalty | null {
+  const collectionBytes = toBytes<C>(collection);
+  const priceBytes = toBytes<P>(price);
+  const packed = memory_read_bytes(import_nft_royalty(
+    changetype<i32>(collectionBytes.dataStart), collectionBytes.byteLength,
+    changetype<i32>(priceBytes.dataStart), priceBytes.byteLength
+  ));
+  if (packed.length == 0) return null;
+  return new NftRoyalty(packed.slice(0, 48), String.UTF8.decode(packed.slice(48).buffer));
+}
+
+export class NftRoyalty {
+  constructor(public recipient: Uint8Array, public amount: string) {}
+}
+
+@external("env", "import_epoch_trainers")
+declare function import_epoch_trainers(height: u64): i32;
+export function epoch_trainers(height: u64 = entry_height()): Uint8Array[] {
+  const packed = memory_read_bytes(import_epoch_trainers(height));
+  const trainers = new Array<Uint8Array>();
+  for (let i = 0; i + 48 <= packed.length; i += 48) {
+    trainers.push(packed.slice(i, i + 48));
+  }
+  return trainers;
+}
+
+@external("env", "import_transfer")
+declare function import_transfer(receiver_ptr: i32, receiver_len: i32, amount_ptr: i32, amount_len: i32, symbol_ptr: i32, symbol_len: i32): void;
+// Sends from the current contract account, amount in flat units
+export function transfer<R, A, S>(receiver: R, amount: A, symbol: S): void {
+  const receiverBytes = toBytes<R>(receiver);
+  const amountBytes = toBytes<A>(amount);
+  const symbolBytes = toBytes<S>(symbol);
+  import_transfer(
+    changetype<i32>(receiverBytes.dataStart), receiverBytes.byteLength,
+    changetype<i32>(amountBytes.dataStart), amountBytes.byteLength,
+    changetype<i32>(symbolBytes.dataStart), symbolBytes.byteLength
+  );
+}
+
+// Runs `func` on this contract at `height`, budget in flat AMA is prepaid from the contract balance
+export function schedule<F>(height: u64, func: F, budget: u64, args: Uint8Array[] = []): void {
+  const fullArgs = new Array<Uint8Array>(3 + args.length);
+  fullArgs[0] = toBytes<u64>(height);
+  fullArgs[1] = toBytes<F>(func);
+  fullArgs[2] = toBytes<u64>(budget);
+  for (let i = 0; i < args.length; i++) { fullArgs[3 + i] = args[i] }
+  call<string, string, Uint8Array>("Schedule", "register", fullArgs);
+}
+
+// Refunds the prepaid budget
+export function cancel_schedule<F>(height: u64, func: F): void {
+  call<string, string, Uint8Array>("Schedule", "cancel", [toBytes<u64>(height), toBytes<F>(func)]);
+}
+
 @external("env", "import_call")
 declare function import_call(args_ptr: i32, extra_args_ptr: i32): i32;
 export function call<C, F, T = Uint8Array>(contract: C, func: F, args: T[], extra_args: T[] | null = null): Uint8Array {
@@ -375,3 +526,25 @@ export function call<C, F, T = Uint8Array>(contract: C, func: F, args: T[], extr
 
   return memory_read_bytes(errorPtr);
 }
+
+@external("env", "import_emit_event")
+declare function import_emit_event(topics_ptr: i32, data_ptr: i32, data_len: i32): void;
+export function emit_event<T, D>(topics: T[], data: D): void {
+  const pinnedTopics = new Array<Uint8Array>(topics.length);
+  for (let i = 0; i < topics.length; i++) { pinnedTopics[i] = toBytes<T>(topics[i]) }
+  const dataBytes = toBytes<D>(data);
+
+  const tablePtr = __alloc(4 + (8 * topics.length));
+  store<i32>(tablePtr, topics.length);
+  for (let i = 0; i < pinnedTopics.length; i++) {
+    const topic = pinnedTopics[i];
+    const offset = 4 + (i * 8);
+
+    store<i32>(tablePtr + offset, changetype<i32>(topic.dataStart));
+    store<i32>(tablePtr + offset + 4, topic.byteLength);
+  }
+
+  import_emit_event(tablePtr as i32, changetype<i32>(dataBytes.dataStart), dataBytes.byteLength);
+
+  __free(tablePtr);
+}
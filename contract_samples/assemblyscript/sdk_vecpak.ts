@@ -61,6 +61,15 @@ export function encodeI16(val: i16, out: Array<u8>): void {
   encodeVarint(<i64>val, out);
 }
 
+export function encodeI64(val: i64, out: Array<u8>): void {
+  out.push(TYPE_INT);
+  encodeVarint(val, out);
+}
+
+export function encodeBool(val: bool, out: Array<u8>): void {
+  out.push(val ? TYPE_TRUE : TYPE_FALSE);
+}
+
 export function encodeString(val: string, out: Array<u8>): void {
   out.push(TYPE_BYTES);
 
@@ -124,6 +133,21 @@ export function decodeI16(data: Uint8Array, ref: DecodeRef): i16 {
   return <i16>decodeVarint(data, ref);
 }
 
+export function decodeI64(data: Uint8Array, ref: DecodeRef): i64 {
+  if (ref.offset >= data.length) throw new Error("EOF reading type");
+  if (data[ref.offset] != TYPE_INT) throw new Error("Expected TYPE_INT");
+  ref.offset++;
+  return decodeVarint(data, ref);
+}
+
+export function decodeBool(data: Uint8Array, ref: DecodeRef): bool {
+  if (ref.offset >= data.length) throw new Error("EOF reading type");
+  const tag = data[ref.offset];
+  if (tag != TYPE_TRUE && tag != TYPE_FALSE) throw new Error("Expected TYPE_TRUE or TYPE_FALSE");
+  ref.offset++;
+  return tag == TYPE_TRUE;
+}
+
 export function decodeString(data: Uint8Array, ref: DecodeRef): string {
   if (ref.offset >= data.length) throw new Error("EOF reading type");
   if (data[ref.offset] != TYPE_BYTES)
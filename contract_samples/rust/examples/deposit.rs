@@ -43,7 +43,7 @@ pub extern "C" fn withdraw(symbol_ptr: i32, amount_ptr: i32) {
 
     kv_increment(key, -withdraw_amount_int);
 
-    call!("Coin", "transfer", [account_caller(), withdraw_amount, withdraw_symbol]);
+    transfer(account_caller(), withdraw_amount, withdraw_symbol);
 
     ret(vault_balance - withdraw_amount_int);
 }
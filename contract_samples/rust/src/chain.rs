@@ -0,0 +1,103 @@
+use crate::Payload;
+use crate::encoding::*;
+use alloc::vec::Vec;
+
+extern "C" {
+    fn import_coin_balance(ap: *const u8, al: usize, sp: *const u8, sl: usize) -> i32;
+    fn import_coin_total_supply(sp: *const u8, sl: usize) -> i32;
+    fn import_nft_balance(ap: *const u8, al: usize, cp: *const u8, cl: usize, tp: *const u8, tl: usize) -> i32;
+    fn import_nft_royalty(cp: *const u8, cl: usize, pp: *const u8, pl: usize) -> i32;
+    fn import_epoch_trainers(height: u64) -> i32;
+
+    fn import_transfer(rp: *const u8, rl: usize, ap: *const u8, al: usize, sp: *const u8, sl: usize);
+}
+
+pub fn coin_balance(address: impl Payload, symbol: impl Payload) -> i128 {
+    let address_cow = address.to_payload();
+    let address_bytes = address_cow.as_ref();
+    let symbol_cow = symbol.to_payload();
+    let symbol_bytes = symbol_cow.as_ref();
+    unsafe {
+        let ptr = import_coin_balance(address_bytes.as_ptr(), address_bytes.len(), symbol_bytes.as_ptr(), symbol_bytes.len());
+        bytes_to_i128(&read_bytes(ptr))
+    }
+}
+
+pub fn coin_total_supply(symbol: impl Payload) -> i128 {
+    let symbol_cow = symbol.to_payload();
+    let symbol_bytes = symbol_cow.as_ref();
+    unsafe {
+        let ptr = import_coin_total_supply(symbol_bytes.as_ptr(), symbol_bytes.len());
+        bytes_to_i128(&read_bytes(ptr))
+    }
+}
+
+pub fn nft_balance(address: impl Payload, collection: impl Payload, token: impl Payload) -> i128 {
+    let address_cow = address.to_payload();
+    let address_bytes = address_cow.as_ref();
+    let collection_cow = collection.to_payload();
+    let collection_bytes = collection_cow.as_ref();
+    let token_cow = token.to_payload();
+    let token_bytes = token_cow.as_ref();
+    unsafe {
+        let ptr = import_nft_balance(
+            address_bytes.as_ptr(), address_bytes.len(),
+            collection_bytes.as_ptr(), collection_bytes.len(),
+            token_bytes.as_ptr(), token_bytes.len()
+        );
+        bytes_to_i128(&read_bytes(ptr))
+    }
+}
+
+// Royalty recipient and amount owed on a sale at price, None when the collection has none
+pub fn nft_royalty(collection: impl Payload, price: impl Payload) -> Option<(Vec<u8>, i128)> {
+    let collection_cow = collection.to_payload();
+    let collection_bytes = collection_cow.as_ref();
+    let price_cow = price.to_payload();
+    let price_bytes = price_cow.as_ref();
+    unsafe {
+        let ptr = import_nft_royalty(collection_bytes.as_ptr(), collection_bytes.len(), price_bytes.as_ptr(), price_bytes.len());
+        let packed = read_bytes(ptr);
+        if packed.is_empty() { return None }
+        Some((packed[..48].to_vec(), bytes_to_i128(&packed[48..])))
+    }
+}
+
+pub fn epoch_trainers(height: u64) -> Vec<Vec<u8>> {
+    unsafe {
+        let ptr = import_epoch_trainers(height);
+        read_bytes(ptr).chunks(48).map(|pk| pk.to_vec()).collect()
+    }
+}
+
+// Sends from the current contract account, amount in flat units
+pub fn transfer(receiver: impl Payload, amount: impl Payload, symbol: impl Payload) {
+    let receiver_cow = receiver.to_payload();
+    let receiver_bytes = receiver_cow.as_ref();
+    let amount_cow = amount.to_payload();
+    let amount_bytes = amount_cow.as_ref();
+    let symbol_cow = symbol.to_payload();
+    let symbol_bytes = symbol_cow.as_ref();
+    unsafe {
+        import_transfer(
+            receiver_bytes.as_ptr(), receiver_bytes.len(),
+            amount_bytes.as_ptr(), amount_bytes.len(),
+            symbol_bytes.as_ptr(), symbol_bytes.len()
+        );
+    }
+}
+
+// Runs `function` on this contract at `height`, budget in flat AMA is prepaid from the contract balance
+pub fn schedule(height: u64, function: impl Payload, budget: i128, args: &[&dyn Payload]) {
+    let mut full_args: Vec<&dyn Payload> = Vec::with_capacity(3 + args.len());
+    full_args.push(&height);
+    full_args.push(&function);
+    full_args.push(&budget);
+    full_args.extend_from_slice(args);
+    crate::context::call("Schedule", "register", &full_args, &[]);
+}
+
+// Refunds the prepaid budget
+pub fn cancel_schedule(height: u64, function: impl Payload) {
+    crate::context::call("Schedule", "cancel", &[&height, &function], &[]);
+}
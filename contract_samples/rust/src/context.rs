@@ -4,25 +4,49 @@ use alloc::{vec, borrow::Cow, vec::Vec};
 
 pub const BURN_ADDRESS: &[u8] = &[0u8; 48];
 
-pub fn seed() -> Vec<u8> { read_bytes(1100) }
-pub fn entry_slot() -> u64 { read_u64(2000) }
-pub fn entry_height() -> u64 { read_u64(2010) }
-pub fn entry_epoch() -> u64 { read_u64(2020) }
-pub fn entry_signer() -> Vec<u8> { read_bytes(2100) }
-pub fn entry_prev_hash() -> Vec<u8> { read_bytes(2200) }
-pub fn entry_vr() -> Vec<u8> { read_bytes(2300) }
-pub fn entry_dr() -> Vec<u8> { read_bytes(2400) }
-pub fn tx_nonce() -> u64 { read_u64(3000) }
-pub fn tx_signer() -> Vec<u8> { read_bytes(3100) }
-pub fn account_current() -> Vec<u8> { read_bytes(4000) }
-pub fn account_caller() -> Vec<u8> { read_bytes(4100) }
-pub fn account_origin() -> Vec<u8> { read_bytes(4200) }
-pub fn attached_symbol() -> Vec<u8> { read_bytes(5000) }
-pub fn attached_amount() -> Vec<u8> { read_bytes(5100) }
+// Memory layout shared with the host, [0, RESERVED_END) is written by the host
+pub mod layout {
+    pub const VERSION: u32 = 1;
+    pub const RESERVED_END: i32 = 65536;
+    pub const VERSION_PTR: i32 = 1024;
+    pub const SEED: i32 = 1100;
+    pub const ENTRY_SLOT: i32 = 2000;
+    pub const ENTRY_HEIGHT: i32 = 2010;
+    pub const ENTRY_EPOCH: i32 = 2020;
+    pub const ENTRY_SIGNER: i32 = 2100;
+    pub const ENTRY_PREV_HASH: i32 = 2200;
+    pub const ENTRY_VR: i32 = 2300;
+    pub const ENTRY_DR: i32 = 2400;
+    pub const TX_NONCE: i32 = 3000;
+    pub const TX_SIGNER: i32 = 3100;
+    pub const ACCOUNT_CURRENT: i32 = 4000;
+    pub const ACCOUNT_CALLER: i32 = 4100;
+    pub const ACCOUNT_ORIGIN: i32 = 4200;
+    pub const ATTACHED_SYMBOL: i32 = 5000;
+    pub const ATTACHED_AMOUNT: i32 = 5100;
+    pub const IO: i32 = 10000;
+}
+
+pub fn layout_version() -> u32 { unsafe { core::ptr::read_unaligned(layout::VERSION_PTR as usize as *const u32) } }
+pub fn seed() -> Vec<u8> { read_bytes(layout::SEED) }
+pub fn entry_slot() -> u64 { read_u64(layout::ENTRY_SLOT) }
+pub fn entry_height() -> u64 { read_u64(layout::ENTRY_HEIGHT) }
+pub fn entry_epoch() -> u64 { read_u64(layout::ENTRY_EPOCH) }
+pub fn entry_signer() -> Vec<u8> { read_bytes(layout::ENTRY_SIGNER) }
+pub fn entry_prev_hash() -> Vec<u8> { read_bytes(layout::ENTRY_PREV_HASH) }
+pub fn entry_vr() -> Vec<u8> { read_bytes(layout::ENTRY_VR) }
+pub fn entry_dr() -> Vec<u8> { read_bytes(layout::ENTRY_DR) }
+pub fn tx_nonce() -> u64 { read_u64(layout::TX_NONCE) }
+pub fn tx_signer() -> Vec<u8> { read_bytes(layout::TX_SIGNER) }
+pub fn account_current() -> Vec<u8> { read_bytes(layout::ACCOUNT_CURRENT) }
+pub fn account_caller() -> Vec<u8> { read_bytes(layout::ACCOUNT_CALLER) }
+pub fn account_origin() -> Vec<u8> { read_bytes(layout::ACCOUNT_ORIGIN) }
+pub fn attached_symbol() -> Vec<u8> { read_bytes(layout::ATTACHED_SYMBOL) }
+pub fn attached_amount() -> Vec<u8> { read_bytes(layout::ATTACHED_AMOUNT) }
 
 pub fn get_attachment() -> (bool, (Vec<u8>, Vec<u8>)) {
     unsafe {
-        let header = core::ptr::read_unaligned(5000 as *const u32);
+        let header = core::ptr::read_unaligned(layout::ATTACHED_SYMBOL as usize as *const u32);
         if header == 0 {
             return (false, (Vec::new(), Vec::new()));
         }
@@ -34,6 +58,7 @@ extern "C" {
     fn import_log(p: *const u8, l: usize);
     fn import_return(p: *const u8, l: usize);
     fn import_call(args_ptr: *const u8, extra_args_ptr: *const u8) -> i32;
+    fn import_emit_event(topics_ptr: *const u8, data_ptr: *const u8, data_len: usize);
 }
 
 pub fn log(line: impl Payload) {
@@ -101,6 +126,28 @@ pub fn call(contract: impl Payload, func: impl Payload, args: &[&dyn Payload], e
     }
 }
 
+pub fn emit_event(topics: &[&dyn Payload], data: impl Payload) {
+    let mut topic_owners = Vec::with_capacity(topics.len());
+    for topic in topics {
+        topic_owners.push(topic.to_payload());
+    }
+    let table = build_table(&topic_owners);
+
+    let data_cow = data.to_payload();
+    let data_bytes = data_cow.as_ref();
+    unsafe { import_emit_event(table.as_ptr(), data_bytes.as_ptr(), data_bytes.len()); }
+}
+
+#[macro_export]
+macro_rules! emit {
+    ([ $( $topic:expr ),* ], $data:expr) => {
+        {
+            let topics_slice: &[&dyn $crate::Payload] = &[ $( &$topic ),* ];
+            $crate::emit_event(topics_slice, $data)
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! call {
     ($contract:expr, $func:expr, [ $( $arg:expr ),* ], [ $( $earg:expr ),* ]) => {
@@ -4,10 +4,13 @@ extern crate alloc;
 pub mod context;
 pub mod storage;
 pub mod encoding;
+pub mod chain;
+pub mod vecpak;
 
 pub use context::*;
 pub use storage::*;
 pub use encoding::*;
+pub use chain::*;
 
 use core::panic::PanicInfo;
 
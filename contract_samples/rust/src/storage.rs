@@ -37,6 +37,7 @@ extern "C" {
     fn import_kv_exists(p: *const u8, l: usize) -> i32;
     fn import_kv_get_prev(pp: *const u8, pl: usize, kp: *const u8, kl: usize) -> i32;
     fn import_kv_get_next(pp: *const u8, pl: usize, kp: *const u8, kl: usize) -> i32;
+    fn import_kv_scan(pp: *const u8, pl: usize, sp: *const u8, sl: usize, ep: *const u8, el: usize, limit: u32, reverse: u32) -> i32;
 
     fn import_kv_put(kp: *const u8, kl: usize, vp: *const u8, vl: usize);
     fn import_kv_increment(kp: *const u8, kl: usize, vp: *const u8, vl: usize) -> i32;
@@ -138,3 +139,33 @@ pub fn kv_get_next(prefix: impl Payload, key: impl Payload) -> (Option<Vec<u8>>,
         }
     }
 }
+
+// Returns up to `limit` pairs in [start, end) under prefix, keys without the prefix
+// Results are capped by the host, continue from the last key if more are expected
+pub fn kv_scan(prefix: impl Payload, start: impl Payload, end: impl Payload, limit: u32, reverse: bool) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let prefix_cow = prefix.to_payload();
+    let prefix_bytes = prefix_cow.as_ref();
+    let start_cow = start.to_payload();
+    let start_bytes = start_cow.as_ref();
+    let end_cow = end.to_payload();
+    let end_bytes = end_cow.as_ref();
+    unsafe {
+        let ptr = import_kv_scan(
+            prefix_bytes.as_ptr(), prefix_bytes.len(),
+            start_bytes.as_ptr(), start_bytes.len(),
+            end_bytes.as_ptr(), end_bytes.len(),
+            limit, reverse as u32
+        );
+        let count = *(ptr as *const u32);
+        let mut pairs = Vec::with_capacity(count as usize);
+        let mut offset = ptr + 4;
+        for _ in 0..count {
+            let key = read_bytes(offset);
+            offset += 4 + key.len() as i32;
+            let value = read_bytes(offset);
+            offset += 4 + value.len() as i32;
+            pairs.push((key, value));
+        }
+        pairs
+    }
+}
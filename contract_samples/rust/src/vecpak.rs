@@ -0,0 +1,125 @@
+use alloc::vec::Vec;
+
+// Typed abi arguments and results are vecpak terms, see the ama_abi custom section
+pub const TYPE_NULL: u8 = 0x00;
+pub const TYPE_TRUE: u8 = 0x01;
+pub const TYPE_FALSE: u8 = 0x02;
+pub const TYPE_INT: u8 = 0x03;
+pub const TYPE_BYTES: u8 = 0x05;
+pub const TYPE_LIST: u8 = 0x06;
+pub const TYPE_MAP: u8 = 0x07;
+
+fn encode_varint(n: i128, out: &mut Vec<u8>) {
+    if n == 0 { out.push(0); return }
+    let mag = n.unsigned_abs().to_be_bytes();
+    let skip = mag.iter().take_while(|b| **b == 0).count();
+    let sign = if n < 0 { 0x80 } else { 0 };
+    out.push(sign | (mag.len() - skip) as u8);
+    out.extend_from_slice(&mag[skip..]);
+}
+
+fn decode_varint(data: &[u8], offset: &mut usize) -> Option<i128> {
+    let header = *data.get(*offset)?;
+    *offset += 1;
+    let len = (header & 0x7f) as usize;
+    if len > 16 { return None }
+    let bytes = data.get(*offset..*offset + len)?;
+    *offset += len;
+    let mag = bytes.iter().fold(0u128, |acc, b| (acc << 8) | *b as u128);
+    Some(if header & 0x80 != 0 { -(mag as i128) } else { mag as i128 })
+}
+
+pub fn encode_int(n: i128, out: &mut Vec<u8>) {
+    out.push(TYPE_INT);
+    encode_varint(n, out);
+}
+
+pub fn encode_bool(v: bool, out: &mut Vec<u8>) {
+    out.push(if v { TYPE_TRUE } else { TYPE_FALSE });
+}
+
+pub fn encode_bytes(v: &[u8], out: &mut Vec<u8>) {
+    out.push(TYPE_BYTES);
+    encode_varint(v.len() as i128, out);
+    out.extend_from_slice(v);
+}
+
+// Follow with `count` encoded items
+pub fn encode_list_header(count: usize, out: &mut Vec<u8>) {
+    out.push(TYPE_LIST);
+    encode_varint(count as i128, out);
+}
+
+// Follow with `count` encoded key, value pairs
+pub fn encode_map_header(count: usize, out: &mut Vec<u8>) {
+    out.push(TYPE_MAP);
+    encode_varint(count as i128, out);
+}
+
+pub fn decode_int(data: &[u8], offset: &mut usize) -> Option<i128> {
+    if *data.get(*offset)? != TYPE_INT { return None }
+    *offset += 1;
+    decode_varint(data, offset)
+}
+
+pub fn decode_bool(data: &[u8], offset: &mut usize) -> Option<bool> {
+    let v = match *data.get(*offset)? {
+        TYPE_TRUE => true,
+        TYPE_FALSE => false,
+        _ => return None,
+    };
+    *offset += 1;
+    Some(v)
+}
+
+pub fn decode_bytes(data: &[u8], offset: &mut usize) -> Option<Vec<u8>> {
+    if *data.get(*offset)? != TYPE_BYTES { return None }
+    *offset += 1;
+    let len = decode_varint(data, offset)? as usize;
+    let bytes = data.get(*offset..*offset + len)?.to_vec();
+    *offset += len;
+    Some(bytes)
+}
+
+// Returns the item count, decode the items that follow
+pub fn decode_list_header(data: &[u8], offset: &mut usize) -> Option<usize> {
+    if *data.get(*offset)? != TYPE_LIST { return None }
+    *offset += 1;
+    Some(decode_varint(data, offset)? as usize)
+}
+
+pub fn decode_map_header(data: &[u8], offset: &mut usize) -> Option<usize> {
+    if *data.get(*offset)? != TYPE_MAP { return None }
+    *offset += 1;
+    Some(decode_varint(data, offset)? as usize)
+}
+
+pub fn int_arg(ptr: i32) -> i128 {
+    decode_int(&crate::encoding::read_bytes(ptr), &mut 0).unwrap_or_else(|| crate::abort!("abi_invalid_int"))
+}
+
+pub fn bool_arg(ptr: i32) -> bool {
+    decode_bool(&crate::encoding::read_bytes(ptr), &mut 0).unwrap_or_else(|| crate::abort!("abi_invalid_bool"))
+}
+
+pub fn bytes_arg(ptr: i32) -> Vec<u8> {
+    decode_bytes(&crate::encoding::read_bytes(ptr), &mut 0).unwrap_or_else(|| crate::abort!("abi_invalid_bytes"))
+}
+
+pub fn ret_int(n: i128) {
+    let mut out = Vec::new();
+    encode_int(n, &mut out);
+    crate::context::ret(out);
+}
+
+pub fn ret_bool(v: bool) {
+    let mut out = Vec::new();
+    encode_bool(v, &mut out);
+    crate::context::ret(out);
+}
+
+pub fn ret_bytes(v: &[u8]) {
+    let mut out = Vec::new();
+    encode_bytes(v, &mut out);
+    crate::context::ret(out);
+}
@@ -18,9 +18,38 @@ defmodule API.Contract do
       view_pk = if view_pk do view_pk else @default_view_pk end
       %{db: db} = :persistent_term.get({:rocksdb, Fabric})
       tip = DB.Chain.tip_entry() |> RDB.vecpak_encode()
+      args = Enum.map(args, &abi_arg/1)
       RDB.contract_view(db, tip, view_pk, contract, function, args, !!Application.fetch_env!(:ama, :testnet))
     end
 
+    def view_profile(contract, function, args, view_pk \\ nil) do
+      view_pk = if view_pk do view_pk else @default_view_pk end
+      %{db: db} = :persistent_term.get({:rocksdb, Fabric})
+      tip = DB.Chain.tip_entry() |> RDB.vecpak_encode()
+      args = Enum.map(args, &abi_arg/1)
+      {success, result, logs, report} = RDB.contract_view_profile(db, tip, view_pk, contract, function, args, !!Application.fetch_env!(:ama, :testnet))
+      report = if report != "", do: RDB.vecpak_decode(report)
+      {success, result, logs, report}
+    end
+
+    #typed abi args, ints and bools travel as ascii and composite terms as vecpak
+    defp abi_arg(arg) when is_binary(arg), do: arg
+    defp abi_arg(arg) when is_integer(arg), do: Integer.to_string(arg)
+    defp abi_arg(arg) when is_boolean(arg), do: Atom.to_string(arg)
+    defp abi_arg(arg), do: RDB.vecpak_encode(arg)
+
+    #typed abi results are already terms, only raw binaries need dumping for json
+    def dump_result(result) when is_binary(result), do: RocksDB.ascii_dump(result)
+    def dump_result(result) when is_list(result), do: Enum.map(result, &dump_result/1)
+    def dump_result(result) when is_map(result), do: Map.new(result, fn {k, v} -> {dump_result(k), dump_result(v)} end)
+    def dump_result(result), do: result
+
+    def abi(contract) do
+      contract = API.maybe_b58(48, contract)
+      %{db: db} = :persistent_term.get({:rocksdb, Fabric})
+      RDB.contract_abi(db, contract)
+    end
+
     def validate(bytecode) do
       %{db: db} = :persistent_term.get({:rocksdb, Fabric})
       tip = DB.Chain.tip_entry() |> RDB.vecpak_encode()
@@ -29,6 +58,25 @@ defmodule API.Contract do
       %{error: error, logs: logs}
     end
 
+    #{cursor, events}, or %{error: :limit_exceeded} past 1000
+    def events(contract, filters \\ %{}) do
+      limit = filters[:limit] || 100
+      if limit > 1000 do %{error: :limit_exceeded} else
+        contract = API.maybe_b58(48, contract)
+        topic = filters[:topic]
+        sort = filters[:sort] || :asc
+
+        %{db: db} = :persistent_term.get({:rocksdb, Fabric})
+        {cursor, events} = RDB.query_event_filter(db, contract, topic, limit, sort == :desc, filters[:cursor])
+        events = Enum.map(events, fn(event)->
+          event = RDB.vecpak_decode(event)
+          %{event | txid: Base58.encode(event.txid), contract: Base58.encode(event.contract)}
+        end)
+        cursor = cursor && Base58.encode(cursor)
+        {cursor, events}
+      end
+    end
+
     def richlist() do
       key = "account:#{:binary.copy(<<0>>, 48)}:balance:AMA"
       {acc, count} = richlist_1(key, {[], 0})
@@ -79,10 +79,12 @@ defmodule API.TX do
             if !next_key do {:halt, {next_key, acc}} else
                 txu = API.TX.get(value)
                 |> put_in([:metadata, :tx_event], :sent)
-                action = TX.action(txu)
+                matches = Enum.any?(TX.actions(txu), fn(action)->
+                    (!filters[:contract] or filters.contract == action.contract)
+                    and (!filters[:function] or filters.function == action.function)
+                end)
                 cond do
-                    !!filters[:contract] and filters.contract != action.contract -> {:cont, {next_key, acc}}
-                    !!filters[:function] and filters.function != action.function -> {:cont, {next_key, acc}}
+                    !matches -> {:cont, {next_key, acc}}
                     true ->
                         acc = acc ++ [txu]
                         if length(acc) >= filters.limit do
@@ -116,10 +118,12 @@ defmodule API.TX do
             if !next_key do {:halt, {next_key, acc}} else
                 txu = API.TX.get(value)
                 |> put_in([:metadata, :tx_event], :recv)
-                action = TX.action(txu)
+                matches = Enum.any?(TX.actions(txu), fn(action)->
+                    (!filters[:contract] or filters.contract == action.contract)
+                    and (!filters[:function] or filters.function == action.function)
+                end)
                 cond do
-                    !!filters[:contract] and filters.contract != action.contract -> {:cont, {next_key, acc}}
-                    !!filters[:function] and filters.function != action.function -> {:cont, {next_key, acc}}
+                    !matches -> {:cont, {next_key, acc}}
                     true ->
                         acc = acc ++ [txu]
                         if length(acc) >= filters.limit do
@@ -174,21 +178,25 @@ defmodule API.TX do
         tx = Map.put(tx, :signature, Base58.encode(tx.signature))
         tx = Map.put(tx, :hash, Base58.encode(tx.hash))
         tx = put_in(tx, [:tx, :signer], Base58.encode(tx.tx.signer))
-
-        action = TX.action(tx)
-        action = if !BlsEx.validate_public_key(action.contract) do action else
-          Map.put(action, :contract, Base58.encode(action.contract))
-        end
-        args = Enum.map(action.args, fn(arg)->
-            cond do
-                !is_binary(arg) or Util.ascii?(arg) -> arg
-                true -> Base58.encode(arg)
-            end
+        tx = if !tx.tx[:sponsor] do tx else put_in(tx, [:tx, :sponsor], Base58.encode(tx.tx.sponsor)) end
+        tx = if !tx[:sponsor_signature] do tx else Map.put(tx, :sponsor_signature, Base58.encode(tx.sponsor_signature)) end
+
+        actions = Enum.map(TX.actions(tx), fn(action)->
+          action = if !BlsEx.validate_public_key(action.contract) do action else
+            Map.put(action, :contract, Base58.encode(action.contract))
+          end
+          args = Enum.map(action.args, fn(arg)->
+              cond do
+                  !is_binary(arg) or Util.ascii?(arg) -> arg
+                  true -> Base58.encode(arg)
+              end
+          end)
+          Map.put(action, :args, args)
         end)
-        action = Map.put(action, :args, args)
 
-        tx = put_in(tx, [:tx, :action], action)
+        tx = put_in(tx, [:tx, :action], hd(actions))
         {_, tx} = pop_in(tx, [:tx, :actions])
+        tx = if length(actions) > 1 do put_in(tx, [:tx, :actions], actions) else tx end
 
         result = tx[:receipt][:result] || tx[:receipt][:error] || tx[:result][:result] || tx[:result][:error]
         success = tx[:receipt][:success] || result == "ok"
@@ -197,6 +205,9 @@ defmodule API.TX do
 
         logs = Enum.map(logs, fn(line)-> RocksDB.ascii_dump(line) end)
         receipt = %{success: success, result: result, logs: logs, exec_used: exec_used}
+        receipt = if !tx[:receipt][:results] do receipt else
+          Map.put(receipt, :results, Enum.map(tx.receipt.results, & RocksDB.ascii_dump(&1)))
+        end
 
         #TODO: remove result later
         tx = Map.put(tx, :result, %{error: result})
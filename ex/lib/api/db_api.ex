@@ -34,7 +34,8 @@ defmodule DB.API do
       "entry", "entry_meta",
       "attestation",
       "tx", "tx_account_nonce", "tx_receiver_nonce", "tx_filter",
-      "contractstate", "contractstate_tree"
+      "contractstate", "contractstate_tree",
+      "event_filter"
     ]
     try do
       {:ok, db_ref, cf_ref_list} = RDB.open_transaction_db(path, cfs)
@@ -45,6 +46,7 @@ defmodule DB.API do
         attestation_cf,
         tx_cf, tx_account_nonce_cf, tx_receiver_nonce_cf, tx_filter_cf,
         contractstate_cf, contractstate_tree_cf,
+        event_filter_cf,
       ] = cf_ref_list
       cf = %{
         default: default_cf,
@@ -52,7 +54,8 @@ defmodule DB.API do
         entry: entry_cf, entry_meta: entry_meta_cf,
         attestation: attestation_cf,
         tx: tx_cf, tx_account_nonce: tx_account_nonce_cf, tx_receiver_nonce: tx_receiver_nonce_cf, tx_filter: tx_filter_cf,
-        contractstate: contractstate_cf, contractstate_tree: contractstate_tree_cf
+        contractstate: contractstate_cf, contractstate_tree: contractstate_tree_cf,
+        event_filter: event_filter_cf
       }
       :persistent_term.put({:rocksdb, Fabric}, %{db: db_ref, cf_list: cf_ref_list, cf: cf, path: path})
     catch
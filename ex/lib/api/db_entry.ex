@@ -108,11 +108,24 @@ defmodule DB.Entry do
       RocksDB.put(key, hash, db_handle(db_opts, :tx_filter, %{}))
     end)
 
+    #Build eventfilter
+    event_filters = RDB.build_event_filters(entry.header.height, receipts)
+    Enum.each(event_filters, fn {key, event} ->
+      RocksDB.put(key, event, db_handle(db_opts, :event_filter, %{}))
+    end)
+
     #Count tx
     old_cnt = RocksDB.get("tx_count", db_handle(db_opts, :sysconf, %{})) || "0"
     new_cnt = :erlang.binary_to_integer(old_cnt) + length(entry.txs)
     RocksDB.put("tx_count", :erlang.integer_to_binary(new_cnt), db_handle(db_opts, :sysconf, %{}))
 
+    #Scheduled callback receipts have no tx to hang off, keep them so a rewind can drop their events
+    tx_hashes = MapSet.new(entry.txs, & &1.hash)
+    scheduled_receipts = Enum.reject(receipts, & MapSet.member?(tx_hashes, &1.txid))
+    if scheduled_receipts != [] do
+      RocksDB.put("entry:#{entry.hash}:scheduled_receipts", RDB.vecpak_encode(scheduled_receipts), db_handle(db_opts, :entry_meta, %{}))
+    end
+
     receipts_by_txid = Map.new(receipts, fn r -> {r.txid, Map.drop(r, [:txid])} end)
     Enum.each(entry.txs, fn(txu)->
       receipt = Map.fetch!(receipts_by_txid, txu.hash)
@@ -171,6 +184,23 @@ defmodule DB.Entry do
       RocksDB.delete(key, db_handle(db_opts, :tx_filter, %{}))
     end)
 
+    #Delete eventfilter
+    receipts = Enum.flat_map(entry.txs, fn(txu)->
+      case RocksDB.get(txu.hash, db_handle(db_opts, :tx, %{})) do
+        nil -> []
+        tx_ptr -> [Map.put(RDB.vecpak_decode(tx_ptr).receipt, :txid, txu.hash)]
+      end
+    end)
+    scheduled_receipts = case RocksDB.get("entry:#{hash}:scheduled_receipts", db_handle(db_opts, :entry_meta, %{})) do
+      nil -> []
+      packed -> RDB.vecpak_decode(packed)
+    end
+    RocksDB.delete("entry:#{hash}:scheduled_receipts", db_handle(db_opts, :entry_meta, %{}))
+    event_filters = RDB.build_event_filters(entry.header.height, receipts ++ scheduled_receipts)
+    Enum.each(event_filters, fn {key, _event} ->
+      RocksDB.delete(key, db_handle(db_opts, :event_filter, %{}))
+    end)
+
     #Decrement tx
     old_cnt = RocksDB.get("tx_count", db_handle(db_opts, :sysconf, %{})) || "0"
     new_cnt = :erlang.binary_to_integer(old_cnt) - length(entry.txs)
@@ -201,18 +231,22 @@ defmodule DB.Entry do
         tx = VanillaSer.decode!(txu.tx_encoded)
         Map.put(txu, :tx, tx)
       end
-      action = TX.action(txu)
-      args = case action.args do
-        [n|t] when is_integer(n) -> [:erlang.integer_to_binary(n) | t]
-        args -> args
-      end
-      args = case action.args do
-        [a,b|t] when is_binary(b) and byte_size(b) == 48 -> [b,a] ++ t
-        args -> args
-      end
+      actions = Enum.map(TX.actions(txu), fn(action)->
+        args = case action.args do
+          [n|t] when is_integer(n) -> [:erlang.integer_to_binary(n) | t]
+          args -> args
+        end
+        args = case action.args do
+          [a,b|t] when is_binary(b) and byte_size(b) == 48 -> [b,a] ++ t
+          args -> args
+        end
+        Map.put(action, :args, args)
+      end)
 
-      txu = put_in(txu, [:tx, :action], action)
-      put_in(txu, [:tx, :action, :args], args)
+      case actions do
+        [action] -> put_in(txu, [:tx, :action], action)
+        actions -> put_in(txu, [:tx, :actions], actions)
+      end
     end)
 
     tx_filters = RDB.build_tx_hashfilters(txs)
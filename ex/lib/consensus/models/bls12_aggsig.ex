@@ -14,6 +14,7 @@ defmodule BLS12AggSig do
     @dst_entry "AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_ENTRY_"
     @dst_vrf "AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_VRF_"
     @dst_tx "AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_TX_"
+    @dst_tx_sponsor "AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_TXSPONSOR_"
     @dst_motion "AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_MOTION_"
     @dst_node "AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NODE_"
     @dst_anr "AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_ANR_"
@@ -25,6 +26,7 @@ defmodule BLS12AggSig do
     def dst_entry(), do: @dst_entry
     def dst_vrf(), do: @dst_vrf
     def dst_tx(), do: @dst_tx
+    def dst_tx_sponsor(), do: @dst_tx_sponsor
     def dst_motion(), do: @dst_motion
     def dst_node(), do: @dst_node
     def dst_anr(), do: @dst_anr
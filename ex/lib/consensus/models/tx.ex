@@ -31,8 +31,8 @@ defmodule TX do
 
    """
 
-   @fields [:tx, :hash, :signature]
-   @fields_tx [:action, :signer, :nonce]
+   @fields [:tx, :hash, :signature, :sponsor_signature]
+   @fields_tx [:action, :actions, :signer, :nonce, :sponsor, :exec_limit, :storage_limit]
    @fields_action [:op, :contract, :function, :args, :attached_symbol, :attached_amount]
 
    def pack(txu) do
@@ -63,19 +63,54 @@ defmodule TX do
       tx = Map.take(txu.tx, @fields_tx)
       true = txu.tx == tx
       txu = put_in(txu, [:tx], tx)
-      action = Map.take(txu.tx.action, @fields_action)
-      true = txu.tx.action == action
-      txu = put_in(txu, [:tx, :action], action)
+
+      #a single action is always encoded as action, several as actions
+      if !!tx[:action] == !!tx[:actions], do: throw(%{error: :action_or_actions_required})
+      if !!tx[:actions] and !is_list(tx.actions), do: throw(%{error: :actions_must_be_list})
+      if !!tx[:actions] and length(tx.actions) < 2, do: throw(%{error: :actions_must_have_multiple})
+      if !!tx[:actions] and length(tx.actions) > RDBProtocol.tx_max_actions(), do: throw(%{error: :too_many_actions})
+      actions = Enum.map(tx[:actions] || [tx.action], & validate_action(&1, is_special_meeting_block))
+      txu = if tx[:actions] do put_in(txu, [:tx, :actions], actions) else put_in(txu, [:tx, :action], hd(actions)) end
 
       tx_encoded = RDB.vecpak_encode(txu.tx)
       if byte_size(tx_encoded) >= Application.fetch_env!(:ama, :tx_size), do: throw(%{error: :too_large})
       if txu.hash != :crypto.hash(:sha256, tx_encoded), do: throw(%{error: :invalid_hash})
       if !BlsEx.verify?(txu.tx.signer, txu.signature, txu.hash, BLS12AggSig.dst_tx()), do: throw(%{error: :invalid_signature})
 
+      #sponsor pays the fees and deposits, both sign the same hash
+      if !!txu.tx[:sponsor] != !!txu[:sponsor_signature], do: throw(%{error: :sponsor_and_signature_required})
+      if !!txu.tx[:sponsor] and (!is_binary(txu.tx.sponsor) or !BlsEx.validate_public_key(txu.tx.sponsor)), do: throw(%{error: :invalid_sponsor})
+      if !!txu.tx[:sponsor] and txu.tx.sponsor == txu.tx.signer, do: throw(%{error: :sponsor_is_signer})
+      if !!txu.tx[:sponsor] and !BlsEx.verify?(txu.tx.sponsor, txu.sponsor_signature, txu.hash, BLS12AggSig.dst_tx_sponsor()), do: throw(%{error: :invalid_sponsor_signature})
+
       #if !!txu.tx[:genesis_hash] and !is_integer(txu.tx.chain_id), do: throw(%{error: :chain_id_not_integer})
       if !is_integer(txu.tx.nonce), do: throw(%{error: :nonce_not_integer})
       if txu.tx.nonce > 18_446_744_073_709_551_615, do: throw(%{error: :nonce_too_high})
+
+      #consensus only honours limits on testnet, elsewhere the default reserve is always debited
+      if !Application.fetch_env!(:ama, :testnet) and (Map.has_key?(txu.tx, :exec_limit) or Map.has_key?(txu.tx, :storage_limit)),
+        do: throw(%{error: :limits_testnet_only})
+      if Map.has_key?(txu.tx, :exec_limit) and !is_integer(txu.tx.exec_limit), do: throw(%{error: :exec_limit_not_integer})
+      if exec_limit(txu) < RDBProtocol.tx_min_exec_limit() or exec_limit(txu) > RDBProtocol.tx_max_exec_limit(), do: throw(%{error: :exec_limit_out_of_bounds})
+      if Map.has_key?(txu.tx, :storage_limit) and !is_integer(txu.tx.storage_limit), do: throw(%{error: :storage_limit_not_integer})
+      if storage_limit(txu) < 0 or storage_limit(txu) > RDBProtocol.tx_max_storage_limit(), do: throw(%{error: :storage_limit_out_of_bounds})
+
+      #if !!txp.tx[:delay] and !is_integer(txp.tx.delay), do: throw %{error: :delay_not_integer}
+      #if !!txp.tx[:delay] and txp.tx.delay <= 0, do: throw %{error: :delay_too_low}
+      #if !!txp.tx[:delay] and txp.tx.delay > 100_000, do: throw %{error: :delay_too_hi}
+
+      throw %{error: :ok, txu: txu}
+    catch
+        :throw,r -> r
+        e,r ->
+          IO.inspect {TX, :validate, e, r}
+          %{error: :unknown, txu: nil}
+    end
+   end
+
+   defp validate_action(action, is_special_meeting_block) do
       if !is_map(action), do: throw(%{error: :action_must_be_map})
+      if Map.take(action, @fields_action) != action, do: throw(%{error: :action_unknown_fields})
       if action[:op] != "call", do: throw %{error: :op_must_be_call}
       if !is_binary(action[:contract]), do: throw %{error: :contract_must_be_binary}
       if !is_binary(action[:function]), do: throw %{error: :function_must_be_binary}
@@ -99,52 +134,68 @@ defmodule TX do
 
       if !!action[:attached_symbol] and !action[:attached_amount], do: throw %{error: :attached_amount_must_be_included}
       if !!action[:attached_amount] and !action[:attached_symbol], do: throw %{error: :attached_symbol_must_be_included}
-
-      #if !!txp.tx[:delay] and !is_integer(txp.tx.delay), do: throw %{error: :delay_not_integer}
-      #if !!txp.tx[:delay] and txp.tx.delay <= 0, do: throw %{error: :delay_too_low}
-      #if !!txp.tx[:delay] and txp.tx.delay > 100_000, do: throw %{error: :delay_too_hi}
-
-      throw %{error: :ok, txu: txu}
-    catch
-        :throw,r -> r
-        e,r ->
-          IO.inspect {TX, :validate, e, r}
-          %{error: :unknown, txu: nil}
-    end
+      action
    end
 
    def build(sk, contract, function, args, nonce \\ nil, attached_symbol \\ nil, attached_amount \\ nil) do
-     pk = BlsEx.get_public_key!(sk)
-     nonce = if !nonce do :os.system_time(:nanosecond) else nonce end
      action = %{op: "call", contract: contract, function: function, args: args}
      action = if is_binary(attached_symbol) and is_binary(attached_amount) do
         Map.merge(action, %{attached_symbol: attached_symbol, attached_amount: attached_amount})
      else action end
-     tx = %{
-        signer: pk,
-        nonce: nonce,
-        action: action
-     }
+     build_actions(sk, [action], nonce)
+   end
+
+   #actions run atomically in order under one nonce and one deposit
+   #TX.build_actions(sk, [%{op: "call", contract: "Coin", function: "transfer", args: [pk, "1", "AMA"]}, ...])
+   #limits is %{exec_limit: amount, storage_limit: amount}, missing keys reserve the protocol default
+   def build_actions(sk, actions, nonce \\ nil, sponsor \\ nil, limits \\ %{}) do
+     pk = BlsEx.get_public_key!(sk)
+     nonce = if !nonce do :os.system_time(:nanosecond) else nonce end
+     tx = case actions do
+        [action] -> %{signer: pk, nonce: nonce, action: action}
+        actions -> %{signer: pk, nonce: nonce, actions: actions}
+     end
+     tx = if sponsor do Map.put(tx, :sponsor, sponsor) else tx end
+     tx = Map.merge(tx, Map.take(limits, [:exec_limit, :storage_limit]))
      tx_encoded = tx |> RDB.vecpak_encode()
      hash = :crypto.hash(:sha256, tx_encoded)
      signature = BlsEx.sign!(sk, hash, BLS12AggSig.dst_tx())
      %{tx: tx, hash: hash, signature: signature}
    end
 
+   #the user builds with the sponsor pk, the sponsor countersigns before submitting
+   #TX.build_actions(user_sk, actions, nil, sponsor_pk) |> TX.sponsor_sign(sponsor_sk)
+   def sponsor_sign(txu, sponsor_sk) do
+     true = BlsEx.get_public_key!(sponsor_sk) == txu.tx.sponsor
+     Map.put(txu, :sponsor_signature, BlsEx.sign!(sponsor_sk, txu.hash, BLS12AggSig.dst_tx_sponsor()))
+   end
+
+   def payer(txu), do: txu.tx[:sponsor] || txu.tx.signer
+
+   def exec_limit(txu), do: txu.tx[:exec_limit] || RDBProtocol.reserve_ama_per_tx_exec()
+   def storage_limit(txu), do: txu.tx[:storage_limit] || RDBProtocol.reserve_ama_per_tx_storage()
+
+   #mirrors protocol::tx_has_custom_limits, without them the legacy reserve applies
+   def custom_limits?(txu) do
+      Application.fetch_env!(:ama, :testnet) and (Map.has_key?(txu.tx, :exec_limit) or Map.has_key?(txu.tx, :storage_limit))
+   end
+
    def valid_pk(pk) do
       pk == BIC.Coin.burn_address() or BlsEx.validate_public_key(pk)
    end
 
    def known_receivers(txu) do
-      action = action(txu)
-      c = action.contract
-      f = action.function
-      a = action.args
-      case {c,f,a} do
-         {"Coin", "transfer", [receiver, _amount, _symbol]} -> valid_pk(receiver) && [receiver]
-         {"Epoch", "slash_trainer", [malicious_pk, _epoch, _signature, _mask_size, _mask]} when byte_size(malicious_pk) == 48 -> valid_pk(malicious_pk) && [malicious_pk]
-         _ -> nil
-      end || []
+      Enum.flat_map(actions(txu), fn(action)->
+        c = action.contract
+        f = action.function
+        a = action.args
+        case {c,f,a} do
+           {"Coin", "transfer", [receiver, _amount, _symbol]} -> valid_pk(receiver) && [receiver]
+           {"Epoch", "slash_trainer", [malicious_pk, _epoch, _signature, _mask_size, _mask]} when byte_size(malicious_pk) == 48 -> valid_pk(malicious_pk) && [malicious_pk]
+           _ -> nil
+        end || []
+      end)
+      |> Enum.uniq()
    end
 
    def exec_cost(epoch, txu) do
@@ -160,4 +211,7 @@ defmodule TX do
 
    def action(%{tx: %{actions: [action|_]}}), do: action
    def action(%{tx: %{action: action}}), do: action
+
+   def actions(%{tx: %{actions: actions}}), do: actions
+   def actions(%{tx: %{action: action}}), do: [action]
 end
@@ -188,19 +188,46 @@ defmodule Ama.MultiServer do
                 {r, key} = Photon.HTTP.read_body_all(state.socket, r)
                 result = API.Contract.get_prefix(key)
                 quick_reply(%{state|request: r}, RDB.vecpak_encode(result))
+            r.method == "POST" and r.path == "/api/contract/view_profile" ->
+                {r, vecpak} = Photon.HTTP.read_body_all(state.socket, r)
+                m = RDB.vecpak_decode(vecpak)
+                {success, result, logs, report} = API.Contract.view_profile(m.contract, m.function, m.args, m[:pk])
+                logs = Enum.map(logs, & RocksDB.ascii_dump(&1))
+                quick_reply(%{state|request: r}, JSX.encode!(%{success: success, result: API.Contract.dump_result(result), logs: logs, profile: report}))
             r.method == "POST" and r.path == "/api/contract/view" ->
                 {r, vecpak} = Photon.HTTP.read_body_all(state.socket, r)
                 m = RDB.vecpak_decode(vecpak)
                 {success, result, logs} = API.Contract.view(m.contract, m.function, m.args, m[:pk])
                 logs = Enum.map(logs, & RocksDB.ascii_dump(&1))
-                quick_reply(%{state|request: r}, JSX.encode!(%{success: success, result: RocksDB.ascii_dump(result), logs: logs}))
+                quick_reply(%{state|request: r}, JSX.encode!(%{success: success, result: API.Contract.dump_result(result), logs: logs}))
             r.method == "GET" and String.starts_with?(r.path, "/api/contract/view") ->
                 [contract, function] = String.replace(r.path, "/api/contract/view/", "") |> :binary.split("/")
                 contract = Base58.decode(contract)
                 query = r.query && Photon.HTTP.parse_query(r.query)
                 {success, result, logs} = API.Contract.view(contract, function, [], query[:pk])
                 logs = Enum.map(logs, & RocksDB.ascii_dump(&1))
-                quick_reply(state, JSX.encode!(%{success: success, result: RocksDB.ascii_dump(result), logs: logs}))
+                quick_reply(state, JSX.encode!(%{success: success, result: API.Contract.dump_result(result), logs: logs}))
+            r.method == "GET" and String.starts_with?(r.path, "/api/contract/abi/") ->
+                contract = String.replace(r.path, "/api/contract/abi/", "")
+                abi = API.Contract.abi(contract)
+                quick_reply(state, %{error: :ok, abi: abi && API.Contract.dump_result(abi)})
+            r.method == "GET" and String.starts_with?(r.path, "/api/contract/events/") ->
+                contract = String.replace(r.path, "/api/contract/events/", "")
+                query = r.query && Photon.HTTP.parse_query(r.query) || %{}
+                filters = %{
+                    topic: query[:topic],
+                    limit: :erlang.binary_to_integer(query[:limit] || "100"),
+                    sort: case query[:sort] do "desc" -> :desc; _ -> :asc end,
+                    cursor: query[:cursor] && Base58.decode(query.cursor),
+                }
+                case API.Contract.events(contract, filters) do
+                    %{error: error} -> quick_reply(state, %{error: error})
+                    {cursor, events} ->
+                        events = Enum.map(events, fn(event)->
+                            %{event | topics: Enum.map(event.topics, & RocksDB.ascii_dump(&1)), data: RocksDB.ascii_dump(event.data)}
+                        end)
+                        quick_reply(state, %{error: :ok, cursor: cursor, events: events})
+                end
             r.method == "GET" and String.starts_with?(r.path, "/api/contract/richlist") ->
                 {richlist, _count} = API.Contract.richlist()
                 quick_reply(state, json_fix_floats(%{error: :ok, richlist: richlist}))
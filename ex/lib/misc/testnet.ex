@@ -23,6 +23,28 @@ defmodule Testnet do
     end
   end
 
+  def upgrade(key, contract, path, migrate_func \\ nil) do
+    wasmbytes = File.read!(path)
+    contract = if byte_size(contract) != 48, do: Base58.decode(contract), else: contract
+    if migrate_func do
+      Testnet.call(key.seed, "Contract", "upgrade", [contract, wasmbytes, migrate_func])
+    else
+      Testnet.call(key.seed, "Contract", "upgrade", [contract, wasmbytes])
+    end
+  end
+
+  #appends the typed abi descriptor as the ama_abi custom section
+  #Testnet.embed_abi("counter.wasm", %{name: "counter", version: "1", functions: [%{name: "get", args: [], returns: "i128"}]})
+  def embed_abi(path, abi) do
+    name = "ama_abi"
+    payload = RDB.vecpak_encode(abi)
+    body = uleb128(byte_size(name)) <> name <> payload
+    File.write!(path, File.read!(path) <> <<0>> <> uleb128(byte_size(body)) <> body)
+  end
+
+  defp uleb128(n) when n < 0x80, do: <<n>>
+  defp uleb128(n), do: <<0x80 + Bitwise.band(n, 0x7f)>> <> uleb128(Bitwise.bsr(n, 7))
+
   def transfer(to, amount, symbol \\ "AMA") do
     key0 = Application.fetch_env!(:ama, :keys) |> Enum.at(0)
     to = if byte_size(to) != 48, do: Base58.decode(to), else: to
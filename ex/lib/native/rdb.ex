@@ -46,7 +46,9 @@ defmodule RDB do
 
   def apply_entry(_db, _entry, _pk, _sk, _testnet, _testnet_peddlebike), do: :erlang.nif_error(:nif_not_loaded)
   def contract_view(_db, _entry, _view_pk, _contract, _function, _args, _testnet), do: :erlang.nif_error(:nif_not_loaded)
+  def contract_view_profile(_db, _entry, _view_pk, _contract, _function, _args, _testnet), do: :erlang.nif_error(:nif_not_loaded)
   def contract_validate(_db, _entry, _wasmbytes, _testnet), do: :erlang.nif_error(:nif_not_loaded)
+  def contract_abi(_db, _contract), do: :erlang.nif_error(:nif_not_loaded)
 
   def vecpak_encode(_map), do: :erlang.nif_error(:nif_not_loaded)
   def vecpak_decode(_bin), do: :erlang.nif_error(:nif_not_loaded)
@@ -65,6 +67,9 @@ defmodule RDB do
   def build_tx_hashfilter(_signer, _arg0, _contract, _function), do: :erlang.nif_error(:nif_not_loaded)
   def build_tx_hashfilters(_txus), do: :erlang.nif_error(:nif_not_loaded)
   def query_tx_hashfilter(_db, _signer, _arg0, _contract, _function, _limit, _sort, _cursor), do: :erlang.nif_error(:nif_not_loaded)
+
+  def build_event_filters(_height, _receipts), do: :erlang.nif_error(:nif_not_loaded)
+  def query_event_filter(_db, _contract, _topic, _limit, _sort, _cursor), do: :erlang.nif_error(:nif_not_loaded)
 end
 
 defmodule RDBProtocol do
@@ -112,4 +117,40 @@ defmodule RDBProtocol do
       const
     end
   end
+
+  def tx_max_actions() do
+    const = :persistent_term.get({ProtocolConstant, :tx_max_actions}, nil)
+    if const do const else
+      const = RDB.protocol_constants().tx_max_actions
+      :persistent_term.put({ProtocolConstant, :tx_max_actions}, const)
+      const
+    end
+  end
+
+  def tx_min_exec_limit() do
+    const = :persistent_term.get({ProtocolConstant, :tx_min_exec_limit}, nil)
+    if const do const else
+      const = RDB.protocol_constants().tx_min_exec_limit
+      :persistent_term.put({ProtocolConstant, :tx_min_exec_limit}, const)
+      const
+    end
+  end
+
+  def tx_max_exec_limit() do
+    const = :persistent_term.get({ProtocolConstant, :tx_max_exec_limit}, nil)
+    if const do const else
+      const = RDB.protocol_constants().tx_max_exec_limit
+      :persistent_term.put({ProtocolConstant, :tx_max_exec_limit}, const)
+      const
+    end
+  end
+
+  def tx_max_storage_limit() do
+    const = :persistent_term.get({ProtocolConstant, :tx_max_storage_limit}, nil)
+    if const do const else
+      const = RDB.protocol_constants().tx_max_storage_limit
+      :persistent_term.put({ProtocolConstant, :tx_max_storage_limit}, const)
+      const
+    end
+  end
 end
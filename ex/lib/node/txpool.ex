@@ -35,12 +35,10 @@ defmodule TXPool do
         chainNonce = DB.Chain.nonce(txu.tx.signer)
         nonceValid = !chainNonce or txu.tx.nonce > chainNonce
 
-        action = TX.action(txu)
-        hasSol = action.function == "submit_sol" and hd(action.args)
-        epochSolValid = if !hasSol do true else
-            <<sol_epoch::32-little, _::binary>> = hasSol
+        epochSolValid = Enum.all?(sols(txu), fn(sol)->
+            <<sol_epoch::32-little, _::binary>> = sol
             cur_epoch == sol_epoch
-        end
+        end)
 
         cond do
             !epochSolValid -> true
@@ -49,6 +47,13 @@ defmodule TXPool do
         end
     end
 
+    #every submit_sol in the tx, a multi action tx can carry it after other actions
+    defp sols(txu) do
+        TX.actions(txu)
+        |> Enum.filter(& &1.function == "submit_sol")
+        |> Enum.map(& hd(&1.args))
+    end
+
     def validate_tx(txu, args \\ %{}) do
       chain_epoch = Map.get_lazy(args, :epoch, fn()-> DB.Chain.epoch() end)
       chain_height = Map.get_lazy(args, :height, fn()-> DB.Chain.height() end)
@@ -62,22 +67,25 @@ defmodule TXPool do
         if !nonceValid, do: throw(%{error: :invalid_tx_nonce, key: {txu.tx.nonce, txu.hash}})
         batch_state = Map.put(batch_state, {:chain_nonce, txu.tx.signer}, txu.tx.nonce)
 
-        balance = Map.get_lazy(batch_state, {:balance, txu.tx.signer}, fn()-> DB.Chain.balance(txu.tx.signer) end)
-        balance = balance - (RDBProtocol.reserve_ama_per_tx_exec() * 2)
-        balance = balance - RDBProtocol.reserve_ama_per_tx_storage()
+        payer = TX.payer(txu)
+        balance = Map.get_lazy(batch_state, {:balance, payer}, fn()-> DB.Chain.balance(payer) end)
+        #custom limits reserve exactly what apply debits, legacy txs keep the old 2x exec margin
+        balance = if TX.custom_limits?(txu) do
+          balance - TX.exec_limit(txu) - TX.storage_limit(txu)
+        else
+          balance - (RDBProtocol.reserve_ama_per_tx_exec() * 2) - RDBProtocol.reserve_ama_per_tx_storage()
+        end
         balance = balance - TX.historical_cost(chain_height, txu)
         if balance < 0, do: throw(%{error: :not_enough_tx_exec_balance, key: {txu.tx.nonce, txu.hash}})
-        batch_state = Map.put(batch_state, {:balance, txu.tx.signer}, balance)
+        batch_state = Map.put(batch_state, {:balance, payer}, balance)
 
-        action = TX.action(txu)
-        hasSol = action.function == "submit_sol" and hd(action.args)
-        epochSolValid = if !hasSol do true else
-          <<sol_epoch::32-little, sol_svrh::32-binary, _::binary>> = hasSol
+        epochSolValid = Enum.all?(sols(txu), fn(sol)->
+          <<sol_epoch::32-little, sol_svrh::32-binary, _::binary>> = sol
 
           chain_epoch == sol_epoch
           and chain_segment_vr_hash == sol_svrh
-          and byte_size(hasSol) == BIC.Sol.size()
-        end
+          and byte_size(sol) == BIC.Sol.size()
+        end)
         if !epochSolValid, do: throw(%{error: :invalid_tx_sol, key: {txu.tx.nonce, txu.hash}})
 
         %{error: :ok, batch_state: batch_state}
@@ -87,10 +87,18 @@ rustler::atoms! {
 
     cost_per_sol,
     cost_per_new_leaf_merkle,
+    tx_max_actions,
+    tx_min_exec_limit,
+    tx_max_exec_limit,
+    tx_max_storage_limit,
 
     txid,
     success,
     exec_used,
     result,
+    results,
     logs,
+    events,
+    topics,
+    data,
 }
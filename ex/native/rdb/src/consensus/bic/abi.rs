@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use std::panic::panic_any;
+use vecpak::{encode, decode, Term};
+use wasmer::wasmparser::{Parser, Payload, ExternalKind, TypeRef, ValType, CompositeInnerType};
+
+use crate::consensus::consensus_apply::ApplyEnv;
+use crate::consensus::consensus_kv::{kv_get, kv_put};
+use crate::bcat;
+use crate::model::_codec::{pl_find_opt, pl_get_bytes_opt};
+
+//contracts opt into typed arguments by embedding a vecpak descriptor in this custom section
+//%{name, version, functions: [%{name, args: [%{name, type}], returns, docs}]}
+pub const SECTION_NAME: &str = "ama_abi";
+pub const MAX_SECTION_SIZE: usize = 16_384;
+pub const TYPES: &[&str] = &["bool", "u64", "i64", "i128", "bytes", "string", "pk", "list", "map", "any"];
+
+#[derive(Clone, Debug, Default)]
+pub struct Abi {
+    pub name: Vec<u8>,
+    pub version: Vec<u8>,
+    pub functions: Vec<AbiFunction>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AbiFunction {
+    pub name: String,
+    pub args: Vec<(Vec<u8>, String)>,
+    pub returns: Option<String>,
+    pub docs: Vec<u8>,
+}
+
+impl Abi {
+    pub fn function(&self, name: &str) -> Option<&AbiFunction> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+}
+
+fn term_string(term: Option<&Term>) -> Result<String, String> {
+    match term {
+        Some(Term::Binary(b)) => String::from_utf8(b.clone()).map_err(|_| "abi_invalid_string".to_string()),
+        _ => Err("abi_missing_field".to_string()),
+    }
+}
+
+fn check_type(ty: &str) -> Result<(), String> {
+    if TYPES.contains(&ty) { Ok(()) } else { Err("abi_unknown_type".to_string()) }
+}
+
+pub fn decode_abi(bytes: &[u8]) -> Result<Abi, String> {
+    let term = decode(bytes).map_err(|_| "abi_invalid_vecpak".to_string())?;
+    let Term::PropList(pairs) = term else { return Err("abi_not_a_map".to_string()) };
+
+    let mut abi = Abi {
+        name: pl_get_bytes_opt(&pairs, b"name").unwrap_or_default().to_vec(),
+        version: pl_get_bytes_opt(&pairs, b"version").unwrap_or_default().to_vec(),
+        functions: Vec::new(),
+    };
+
+    let Some(Term::List(functions)) = pl_find_opt(&pairs, b"functions") else { return Err("abi_missing_functions".to_string()) };
+    let mut seen = HashSet::new();
+    for function in functions {
+        let Term::PropList(fpairs) = function else { return Err("abi_function_not_a_map".to_string()) };
+        let name = term_string(pl_find_opt(fpairs, b"name"))?;
+        if !seen.insert(name.clone()) { return Err("abi_duplicate_function".to_string()) }
+
+        let mut args = Vec::new();
+        match pl_find_opt(fpairs, b"args") {
+            None => {},
+            Some(Term::List(list)) => {
+                for arg in list {
+                    let Term::PropList(apairs) = arg else { return Err("abi_arg_not_a_map".to_string()) };
+                    let ty = term_string(pl_find_opt(apairs, b"type"))?;
+                    check_type(&ty)?;
+                    args.push((pl_get_bytes_opt(apairs, b"name").unwrap_or_default().to_vec(), ty));
+                }
+            },
+            Some(_) => return Err("abi_args_not_a_list".to_string()),
+        }
+
+        let returns = match pl_find_opt(fpairs, b"returns") {
+            None => None,
+            Some(t) => { let ty = term_string(Some(t))?; check_type(&ty)?; Some(ty) }
+        };
+
+        abi.functions.push(AbiFunction {
+            name, args, returns,
+            docs: pl_get_bytes_opt(fpairs, b"docs").unwrap_or_default().to_vec(),
+        });
+    }
+    Ok(abi)
+}
+
+pub fn encode_abi(abi: &Abi) -> Vec<u8> {
+    encode(Term::PropList(vec![
+        (Term::Binary(b"name".to_vec()), Term::Binary(abi.name.clone())),
+        (Term::Binary(b"version".to_vec()), Term::Binary(abi.version.clone())),
+        (Term::Binary(b"functions".to_vec()), Term::List(abi.functions.iter().map(|f| {
+            let mut pairs = vec![
+                (Term::Binary(b"name".to_vec()), Term::Binary(f.name.clone().into_bytes())),
+                (Term::Binary(b"args".to_vec()), Term::List(f.args.iter().map(|(name, ty)| Term::PropList(vec![
+                    (Term::Binary(b"name".to_vec()), Term::Binary(name.clone())),
+                    (Term::Binary(b"type".to_vec()), Term::Binary(ty.clone().into_bytes())),
+                ])).collect())),
+            ];
+            if let Some(ret) = &f.returns {
+                pairs.push((Term::Binary(b"returns".to_vec()), Term::Binary(ret.clone().into_bytes())));
+            }
+            if !f.docs.is_empty() {
+                pairs.push((Term::Binary(b"docs".to_vec()), Term::Binary(f.docs.clone())));
+            }
+            Term::PropList(pairs)
+        }).collect())),
+    ]))
+}
+
+//raw section payload, None when the contract is untyped
+pub fn section(wasm_bytes: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let mut found = None;
+    for payload in Parser::new(0).parse_all(wasm_bytes) {
+        if let Payload::CustomSection(reader) = payload.map_err(|e| e.to_string())? {
+            if reader.name() != SECTION_NAME { continue }
+            if found.is_some() { return Err("abi_duplicate_section".to_string()) }
+            if reader.data().len() > MAX_SECTION_SIZE { return Err("abi_section_too_large".to_string()) }
+            found = Some(reader.data().to_vec());
+        }
+    }
+    Ok(found)
+}
+
+//exported functions with their param types, in export order
+pub fn exports(wasm_bytes: &[u8]) -> Result<Vec<(String, Vec<ValType>)>, String> {
+    let mut types: Vec<Vec<ValType>> = Vec::new();
+    let mut func_types: Vec<u32> = Vec::new();
+    let mut exported: Vec<(String, u32)> = Vec::new();
+
+    for payload in Parser::new(0).parse_all(wasm_bytes) {
+        match payload.map_err(|e| e.to_string())? {
+            Payload::TypeSection(reader) => {
+                for group in reader {
+                    for sub in group.map_err(|e| e.to_string())?.into_types() {
+                        match sub.composite_type.inner {
+                            CompositeInnerType::Func(f) => types.push(f.params().to_vec()),
+                            _ => types.push(Vec::new()),
+                        }
+                    }
+                }
+            },
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if let TypeRef::Func(idx) = import.map_err(|e| e.to_string())?.ty { func_types.push(idx) }
+                }
+            },
+            Payload::FunctionSection(reader) => {
+                for idx in reader { func_types.push(idx.map_err(|e| e.to_string())?) }
+            },
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let e = export.map_err(|e| e.to_string())?;
+                    if e.kind == ExternalKind::Func { exported.push((e.name.to_string(), e.index)) }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    exported.into_iter().map(|(name, index)| {
+        let params = func_types.get(index as usize)
+            .and_then(|t| types.get(*t as usize))
+            .cloned()
+            .ok_or_else(|| "abi_export_type_not_found".to_string())?;
+        Ok((name, params))
+    }).collect()
+}
+
+//every described function must be exported and take one pointer per declared arg
+pub fn validate(wasm_bytes: &[u8]) -> Result<Option<Abi>, String> {
+    let Some(bytes) = section(wasm_bytes)? else { return Ok(None) };
+    let abi = decode_abi(&bytes)?;
+    let exports = exports(wasm_bytes)?;
+    for function in &abi.functions {
+        let Some((_, params)) = exports.iter().find(|(name, _)| *name == function.name) else {
+            return Err("abi_function_not_exported".to_string())
+        };
+        if params.len() != function.args.len() { return Err("abi_arg_count_mismatch".to_string()) }
+        if params.iter().any(|p| *p != ValType::I32) { return Err("abi_arg_not_pointer".to_string()) }
+    }
+    Ok(Some(abi))
+}
+
+//registry record kept next to the bytecode so explorers and wallets can introspect without parsing wasm
+//%{exports: [%{name, arity}], name, version, functions}
+pub fn registry_entry(wasm_bytes: &[u8]) -> Vec<u8> {
+    let abi = validate(wasm_bytes).unwrap_or_else(|e| panic_any(e)).unwrap_or_default();
+    let exports = exports(wasm_bytes).unwrap_or_else(|e| panic_any(e));
+
+    let Term::PropList(mut pairs) = decode(&encode_abi(&abi)).unwrap() else { unreachable!() };
+    pairs.insert(0, (Term::Binary(b"exports".to_vec()), Term::List(exports.iter().map(|(name, params)| Term::PropList(vec![
+        (Term::Binary(b"name".to_vec()), Term::Binary(name.clone().into_bytes())),
+        (Term::Binary(b"arity".to_vec()), Term::VarInt(params.len() as i128)),
+    ])).collect())));
+    encode(Term::PropList(pairs))
+}
+
+pub fn put(env: &mut ApplyEnv, contract: &[u8], wasm_bytes: &[u8]) {
+    kv_put(env, &bcat(&[b"account:", contract, b":attribute:abi"]), &registry_entry(wasm_bytes));
+}
+
+pub fn lookup(env: &mut ApplyEnv, contract: &[u8]) -> Option<Abi> {
+    match kv_get(env, &bcat(&[b"account:", contract, b":attribute:abi"])) {
+        Some(entry) => decode_abi(&entry).ok(),
+        //contracts deployed before the registry existed
+        None => {
+            let bytecode = crate::consensus::bic::contract::bytecode(env, contract)?;
+            section(&bytecode).ok().flatten().and_then(|bytes| decode_abi(&bytes).ok())
+        }
+    }
+}
+
+fn term_matches(ty: &str, term: &Term) -> bool {
+    match (ty, term) {
+        ("any", _) => true,
+        ("bool", Term::Bool(_)) => true,
+        ("u64", Term::VarInt(v)) => *v >= 0 && *v <= u64::MAX as i128,
+        ("i64", Term::VarInt(v)) => *v >= i64::MIN as i128 && *v <= i64::MAX as i128,
+        ("i128", Term::VarInt(_)) => true,
+        ("bytes", Term::Binary(_)) => true,
+        ("string", Term::Binary(b)) => std::str::from_utf8(b).is_ok(),
+        ("pk", Term::Binary(b)) => b.len() == 48,
+        ("list", Term::List(_)) => true,
+        ("map", Term::PropList(_)) => true,
+        _ => false,
+    }
+}
+
+//callers hand over raw bytes, ints as ascii and composite types already vecpak encoded
+pub fn coerce_arg(ty: &str, raw: &[u8]) -> Vec<u8> {
+    let term = match ty {
+        "bool" => match raw {
+            b"true" => Term::Bool(true),
+            b"false" => Term::Bool(false),
+            _ => panic_any("abi_invalid_bool"),
+        },
+        "u64" | "i64" | "i128" => {
+            let v = std::str::from_utf8(raw).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("abi_invalid_int"));
+            Term::VarInt(v)
+        },
+        "bytes" | "string" | "pk" => Term::Binary(raw.to_vec()),
+        _ => decode(raw).unwrap_or_else(|_| panic_any("abi_invalid_vecpak")),
+    };
+    if !term_matches(ty, &term) { panic_any("abi_arg_type_mismatch") }
+    encode(term)
+}
+
+pub fn coerce_args(function: &AbiFunction, args: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    if args.len() != function.args.len() { panic_any("abi_arg_count_mismatch") }
+    function.args.iter().zip(args).map(|((_, ty), raw)| coerce_arg(ty, raw)).collect()
+}
+
+pub fn check_result(function: &AbiFunction, result: &[u8]) {
+    let Some(ty) = &function.returns else { return };
+    let term = decode(result).unwrap_or_else(|_| panic_any("abi_invalid_result"));
+    if !term_matches(ty, &term) { panic_any("abi_result_type_mismatch") }
+}
@@ -1,6 +1,6 @@
 use std::panic::panic_any;
 use crate::{bcat, consensus};
-use crate::consensus::consensus_kv::{kv_get, kv_put, kv_increment, kv_exists};
+use crate::consensus::consensus_kv::{kv_get, kv_put, kv_increment, kv_exists, kv_delete};
 use vecpak::{encode, decode, Term};
 
 pub const DECIMALS: u32 = 9;
@@ -61,6 +61,27 @@ pub fn soulbound(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol:
     }
 }
 
+pub fn frozen(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], account: &[u8]) -> bool {
+    kv_get(env, &bcat(&[b"coin:", symbol, b":frozen:", account])).as_deref() == Some(b"true")
+}
+
+pub fn allowlist_mode(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8]) -> bool {
+    kv_get(env, &bcat(&[b"coin:", symbol, b":allowlist_mode"])).as_deref() == Some(b"true")
+}
+
+pub fn allowlisted(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], account: &[u8]) -> bool {
+    kv_get(env, &bcat(&[b"coin:", symbol, b":allowlist:", account])).as_deref() == Some(b"true")
+}
+
+//compliance controls only exist on pausable coins, burning is never blocked by the allowlist
+//AMA and mainnet return before any read so existing transfers keep their exact cost
+pub fn check_account(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], account: &[u8]) {
+    if !env.testnet || symbol == b"AMA" || account == &BURN_ADDRESS { return }
+    if !pausable(env, symbol) { return }
+    if frozen(env, symbol, account) { panic_any("account_frozen") }
+    if allowlist_mode(env, symbol) && !allowlisted(env, symbol, account) { panic_any("account_not_allowlisted") }
+}
+
 pub fn total_supply(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8]) -> i128 {
     match kv_get(env, &bcat(&[b"coin:", symbol, b":totalSupply"])) {
         Some(amount) => std::str::from_utf8(&amount).unwrap().parse::<i128>().unwrap_or_else(|_| panic_any("invalid_total_supply")),
@@ -68,6 +89,12 @@ pub fn total_supply(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbo
     }
 }
 
+//None when the coin was created without a cap
+pub fn max_supply(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8]) -> Option<i128> {
+    kv_get(env, &bcat(&[b"coin:", symbol, b":maxSupply"]))
+        .map(|amount| std::str::from_utf8(&amount).unwrap().parse::<i128>().unwrap_or_else(|_| panic_any("invalid_max_supply")))
+}
+
 pub fn exists(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8]) -> bool {
     match kv_get(env, &bcat(&[b"coin:", symbol, b":totalSupply"])) {
         Some(_) => true,
@@ -75,21 +102,164 @@ pub fn exists(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u
     }
 }
 
-pub fn has_permission(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], signer: &[u8]) -> bool {
-    match kv_get(env, &bcat(&[b"coin:", symbol, b":permission"])) {
-        None => false,
-        Some(permission_list) => {
-            let term = decode(permission_list.as_slice()).unwrap();
-            match term {
-                Term::List(term_list) => term_list.iter().any(|el| {
-                    matches!(el, Term::Binary(b) if b.as_slice() == signer)
-                }),
-                _ => false
-            }
-        }
+//admin is the legacy permission list, minter and pauser are granted by admins
+//the owner alone manages admins and can hand over or renounce ownership
+pub const ROLES: &[&[u8]] = &[b"admin", b"minter", b"pauser"];
+
+fn role_key(symbol: &[u8], role: &[u8]) -> Vec<u8> {
+    match role {
+        b"admin" => bcat(&[b"coin:", symbol, b":permission"]),
+        _ => bcat(&[b"coin:", symbol, b":role:", role]),
+    }
+}
+
+fn role_members(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], role: &[u8]) -> Vec<Vec<u8>> {
+    match kv_get(env, &role_key(symbol, role)).map(|v| decode(v.as_slice())) {
+        Some(Ok(Term::List(term_list))) => term_list.into_iter().filter_map(|el| match el {
+            Term::Binary(b) => Some(b),
+            _ => None
+        }).collect(),
+        _ => Vec::new()
     }
 }
 
+fn put_role_members(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], role: &[u8], members: Vec<Vec<u8>>) {
+    if members.is_empty() {
+        kv_delete(env, &role_key(symbol, role));
+    } else {
+        kv_put(env, &role_key(symbol, role), &encode(Term::List(members.into_iter().map(Term::Binary).collect())));
+    }
+}
+
+pub fn owner(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8]) -> Option<Vec<u8>> {
+    match kv_get(env, &bcat(&[b"coin:", symbol, b":owner"])) {
+        Some(owner) => Some(owner),
+        //coins created before ownership was tracked, the creator is the first admin
+        None if !kv_exists(env, &bcat(&[b"coin:", symbol, b":renounced"])) => role_members(env, symbol, b"admin").into_iter().next(),
+        None => None
+    }
+}
+
+//admins hold every role
+pub fn has_role(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], role: &[u8], account: &[u8]) -> bool {
+    if role_members(env, symbol, b"admin").iter().any(|m| m.as_slice() == account) { return true }
+    role != b"admin" && role_members(env, symbol, role).iter().any(|m| m.as_slice() == account)
+}
+
+pub fn has_permission(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], signer: &[u8]) -> bool {
+    has_role(env, symbol, b"admin", signer)
+}
+
+fn check_role_change(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], role: &[u8]) {
+    if !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+    if !ROLES.contains(&role) { panic_any("invalid_role") }
+    let caller = env.caller_env.account_caller.clone();
+    let allowed = match role {
+        b"admin" => owner(env, symbol).as_deref() == Some(caller.as_slice()),
+        _ => has_permission(env, symbol, &caller),
+    };
+    if !allowed { panic_any("no_permissions") }
+}
+
+fn emit_role(env: &mut crate::consensus::consensus_apply::ApplyEnv, topic: &[u8], symbol: &[u8], role: &[u8], account: &[u8]) {
+    let caller = env.caller_env.account_caller.clone();
+    consensus::bic::emit_event(env, b"Coin", vec![topic.to_vec(), symbol.to_vec(), role.to_vec(), account.to_vec()], caller);
+}
+
+//topic, symbol, previous owner, new owner (empty once renounced)
+fn emit_ownership(env: &mut crate::consensus::consensus_apply::ApplyEnv, topic: &[u8], symbol: &[u8], previous_owner: &[u8], new_owner: &[u8]) {
+    let caller = env.caller_env.account_caller.clone();
+    consensus::bic::emit_event(env, b"Coin", vec![topic.to_vec(), symbol.to_vec(), previous_owner.to_vec(), new_owner.to_vec()], caller);
+}
+
+pub fn call_grant_role(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    let role = args[1].as_slice();
+    let account = args[2].as_slice();
+    if account.len() != 48 || !consensus::bls12_381::validate_public_key(account) { panic_any("invalid_account_pk") }
+    check_role_change(env, symbol, role);
+
+    let mut members = role_members(env, symbol, role);
+    if members.iter().any(|m| m.as_slice() == account) { panic_any("role_already_granted") }
+    members.push(account.to_vec());
+    put_role_members(env, symbol, role, members);
+    emit_role(env, b"role_granted", symbol, role, account);
+}
+
+pub fn call_revoke_role(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    let role = args[1].as_slice();
+    let account = args[2].as_slice();
+    check_role_change(env, symbol, role);
+    if role == b"admin" && owner(env, symbol).as_deref() == Some(account) { panic_any("cannot_revoke_owner") }
+
+    revoke(env, symbol, role, account);
+}
+
+//any holder can drop their own role, the owner renounces through renounce_ownership
+pub fn call_renounce_role(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 2 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    let role = args[1].as_slice();
+    let caller = env.caller_env.account_caller.clone();
+    if !ROLES.contains(&role) { panic_any("invalid_role") }
+    if role == b"admin" && owner(env, symbol).as_deref() == Some(caller.as_slice()) { panic_any("owner_must_renounce_ownership") }
+
+    revoke(env, symbol, role, &caller);
+}
+
+fn revoke(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], role: &[u8], account: &[u8]) {
+    let mut members = role_members(env, symbol, role);
+    let len = members.len();
+    members.retain(|m| m.as_slice() != account);
+    if members.len() == len { panic_any("role_not_granted") }
+    put_role_members(env, symbol, role, members);
+    emit_role(env, b"role_revoked", symbol, role, account);
+}
+
+//symbol, new_owner, [keep_admin]; the previous owner loses admin unless keep_admin is "true"
+pub fn call_transfer_ownership(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 2 && args.len() != 3 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    let new_owner = args[1].as_slice();
+    let keep_admin = match args.get(2).map(|a| a.as_slice()) {
+        None | Some(b"false") => false,
+        Some(b"true") => true,
+        _ => panic_any("invalid_keep_admin")
+    };
+    let caller = env.caller_env.account_caller.clone();
+    if !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+    if owner(env, symbol).as_deref() != Some(caller.as_slice()) { panic_any("not_owner") }
+    if new_owner.len() != 48 || !consensus::bls12_381::validate_public_key(new_owner) { panic_any("invalid_owner_pk") }
+
+    let mut admins = role_members(env, symbol, b"admin");
+    let revoke = !keep_admin && caller.as_slice() != new_owner;
+    if revoke { admins.retain(|m| m.as_slice() != caller.as_slice()) }
+    if !admins.iter().any(|m| m.as_slice() == new_owner) { admins.push(new_owner.to_vec()) }
+    put_role_members(env, symbol, b"admin", admins);
+    kv_put(env, &bcat(&[b"coin:", symbol, b":owner"]), new_owner);
+    if revoke { emit_role(env, b"role_revoked", symbol, b"admin", &caller) }
+    emit_ownership(env, b"ownership_transferred", symbol, &caller, new_owner);
+}
+
+//drops ownership and the caller admin role, admins can no longer be added
+pub fn call_renounce_ownership(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    let caller = env.caller_env.account_caller.clone();
+    if !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+    if owner(env, symbol).as_deref() != Some(caller.as_slice()) { panic_any("not_owner") }
+
+    let mut admins = role_members(env, symbol, b"admin");
+    admins.retain(|m| m.as_slice() != caller.as_slice());
+    put_role_members(env, symbol, b"admin", admins);
+    kv_delete(env, &bcat(&[b"coin:", symbol, b":owner"]));
+    kv_put(env, &bcat(&[b"coin:", symbol, b":renounced"]), b"true");
+    emit_ownership(env, b"ownership_renounced", symbol, &caller, b"");
+}
+
 pub fn call_transfer(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
     if args.len() != 3 { panic_any("invalid_args") }
     let receiver = args[0].as_slice();
@@ -97,15 +267,22 @@ pub fn call_transfer(env: &mut crate::consensus::consensus_apply::ApplyEnv, args
     let amount = std::str::from_utf8(&amount).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
     let symbol = args[2].as_slice();
 
+    let sender = env.caller_env.account_caller.clone();
+    transfer(env, &sender, receiver, amount, symbol);
+}
+
+pub fn transfer(env: &mut crate::consensus::consensus_apply::ApplyEnv, sender: &[u8], receiver: &[u8], amount: i128, symbol: &[u8]) {
     if receiver.len() != 48 { panic_any("invalid_receiver_pk") }
     if !(consensus::bls12_381::validate_public_key(receiver) || receiver == &BURN_ADDRESS) { panic_any("invalid_receiver_pk") }
     if amount <= 0 { panic_any("invalid_amount") }
-    if amount > balance(env, &env.caller_env.account_caller.clone(), &symbol) { panic_any("insufficient_funds") }
+    if amount > balance(env, sender, symbol) { panic_any("insufficient_funds") }
 
     if paused(env, symbol) { panic_any("paused") }
     if soulbound(env, symbol) { panic_any("soulbound") }
+    check_account(env, symbol, sender);
+    check_account(env, symbol, receiver);
 
-    kv_increment(env, &bcat(&[b"account:", &env.caller_env.account_caller, b":balance:", symbol]), -amount);
+    kv_increment(env, &bcat(&[b"account:", sender, b":balance:", symbol]), -amount);
     kv_increment(env, &bcat(&[b"account:", receiver, b":balance:", symbol]), amount);
 
     //Account burnt coins
@@ -114,6 +291,86 @@ pub fn call_transfer(env: &mut crate::consensus::consensus_apply::ApplyEnv, args
     }
 }
 
+fn allowance_key(owner: &[u8], spender: &[u8], symbol: &[u8]) -> Vec<u8> {
+    bcat(&[b"account:", owner, b":allowance:", spender, b":", symbol])
+}
+
+pub fn allowance(env: &mut crate::consensus::consensus_apply::ApplyEnv, owner: &[u8], spender: &[u8], symbol: &[u8]) -> i128 {
+    match kv_get(env, &allowance_key(owner, spender, symbol)) {
+        Some(amount) => std::str::from_utf8(&amount).unwrap().parse::<i128>().unwrap_or_else(|_| panic_any("invalid_allowance")),
+        None => 0
+    }
+}
+
+fn set_allowance(env: &mut crate::consensus::consensus_apply::ApplyEnv, owner: &[u8], spender: &[u8], symbol: &[u8], amount: i128) {
+    if amount == 0 {
+        kv_delete(env, &allowance_key(owner, spender, symbol));
+    } else {
+        kv_put(env, &allowance_key(owner, spender, symbol), amount.to_string().as_bytes());
+    }
+}
+
+//spender, amount, symbol with the caller as owner
+fn parse_allowance_args(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> (Vec<u8>, i128, Vec<u8>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let spender = args[0].as_slice();
+    let amount = std::str::from_utf8(&args[1]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    let symbol = args[2].as_slice();
+
+    if spender.len() != 48 || !consensus::bls12_381::validate_public_key(spender) { panic_any("invalid_spender_pk") }
+    if spender == env.caller_env.account_caller.as_slice() { panic_any("spender_is_owner") }
+    if amount < 0 { panic_any("invalid_amount") }
+    if symbol != b"AMA" && !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+    if soulbound(env, symbol) { panic_any("soulbound") }
+    (spender.to_vec(), amount, symbol.to_vec())
+}
+
+pub fn call_approve(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    let (spender, amount, symbol) = parse_allowance_args(env, &args);
+    let owner = env.caller_env.account_caller.clone();
+    set_allowance(env, &owner, &spender, &symbol, amount);
+}
+
+pub fn call_increase_allowance(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    let (spender, amount, symbol) = parse_allowance_args(env, &args);
+    let owner = env.caller_env.account_caller.clone();
+    let current = allowance(env, &owner, &spender, &symbol);
+    let amount = current.checked_add(amount).unwrap_or_else(|| panic_any("allowance_overflow"));
+    set_allowance(env, &owner, &spender, &symbol, amount);
+}
+
+pub fn call_decrease_allowance(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    let (spender, amount, symbol) = parse_allowance_args(env, &args);
+    let owner = env.caller_env.account_caller.clone();
+    let current = allowance(env, &owner, &spender, &symbol);
+    if amount > current { panic_any("allowance_below_zero") }
+    set_allowance(env, &owner, &spender, &symbol, current - amount);
+}
+
+//caller spends from the owner balance up to the approved allowance
+pub fn call_transfer_from(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 4 { panic_any("invalid_args") }
+    let owner = args[0].as_slice();
+    let receiver = args[1].as_slice();
+    let amount = std::str::from_utf8(&args[2]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    let symbol = args[3].as_slice();
+    let spender = env.caller_env.account_caller.clone();
+
+    if owner.len() != 48 { panic_any("invalid_owner_pk") }
+    if amount <= 0 { panic_any("invalid_amount") }
+    let current = allowance(env, owner, &spender, symbol);
+    if amount > current { panic_any("insufficient_allowance") }
+    check_account(env, symbol, &spender);
+    set_allowance(env, owner, &spender, symbol, current - amount);
+
+    transfer(env, owner, receiver, amount, symbol);
+}
+
+pub fn view_allowance(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    if args.len() != 3 { panic_any("invalid_args") }
+    encode(Term::VarInt(allowance(env, &args[0], &args[1], &args[2])))
+}
+
 pub fn call_create_and_mint(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
     if args.len() < 2 { panic_any("invalid_args") }
     let symbol_original = args[0].as_slice();
@@ -122,6 +379,9 @@ pub fn call_create_and_mint(env: &mut crate::consensus::consensus_apply::ApplyEn
     let mintable = args.get(3).and_then(|v| if v.is_empty() { None } else { Some(v.as_slice()) }).unwrap_or(b"false");
     let pausable = args.get(4).and_then(|v| if v.is_empty() { None } else { Some(v.as_slice()) }).unwrap_or(b"false");
     let soulbound = args.get(5).and_then(|v| if v.is_empty() { None } else { Some(v.as_slice()) }).unwrap_or(b"false");
+    let max_supply = args.get(6).and_then(|v| if v.is_empty() { None } else { Some(v.as_slice()) });
+    let name = args.get(7).map(|v| v.as_slice()).unwrap_or(b"");
+    let uri = args.get(8).map(|v| v.as_slice()).unwrap_or(b"");
 
     let symbol: Vec<u8> = symbol_original.iter().copied().filter(u8::is_ascii_alphanumeric).collect();
     if symbol_original != symbol.as_slice() { panic_any("invalid_symbol") }
@@ -138,6 +398,12 @@ pub fn call_create_and_mint(env: &mut crate::consensus::consensus_apply::ApplyEn
     //if decimals < 0 { panic_any("invalid_decimals") }
     if decimals >= 10 { panic_any("invalid_decimals") }
 
+    let max_supply = max_supply.map(|v| std::str::from_utf8(v).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_max_supply")));
+    if let Some(max_supply) = max_supply {
+        if amount > max_supply { panic_any("exceeds_max_supply") }
+        kv_put(env, &bcat(&[b"coin:", &symbol, b":maxSupply"]), max_supply.to_string().as_bytes());
+    }
+
     kv_increment(env, &bcat(&[b"account:", &env.caller_env.account_caller, b":balance:", &symbol]), amount);
     kv_increment(env, &bcat(&[b"coin:", &symbol, b":totalSupply"]), amount);
 
@@ -145,10 +411,85 @@ pub fn call_create_and_mint(env: &mut crate::consensus::consensus_apply::ApplyEn
     admin.push(Term::Binary(env.caller_env.account_caller.to_vec()));
     let buf = encode(Term::List(admin));
     kv_put(env, &bcat(&[b"coin:", &symbol, b":permission"]), &buf);
+    kv_put(env, &bcat(&[b"coin:", &symbol, b":owner"]), &env.caller_env.account_caller.clone());
 
     if mintable == b"true" { kv_put(env, &bcat(&[b"coin:", &symbol, b":mintable"]), b"true") }
     if pausable == b"true" { kv_put(env, &bcat(&[b"coin:", &symbol, b":pausable"]), b"true") }
     if soulbound == b"true" { kv_put(env, &bcat(&[b"coin:", &symbol, b":soulbound"]), b"true") }
+
+    kv_put(env, &bcat(&[b"coin:", &symbol, b":decimals"]), decimals.to_string().as_bytes());
+    put_metadata(env, &symbol, name, uri);
+}
+
+fn put_metadata(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], name: &[u8], uri: &[u8]) {
+    if name.len() > 64 || std::str::from_utf8(name).is_err() { panic_any("invalid_name") }
+    if uri.len() > 256 || std::str::from_utf8(uri).is_err() { panic_any("invalid_uri") }
+    if name.is_empty() { kv_delete(env, &bcat(&[b"coin:", symbol, b":name"])) } else { kv_put(env, &bcat(&[b"coin:", symbol, b":name"]), name) }
+    if uri.is_empty() { kv_delete(env, &bcat(&[b"coin:", symbol, b":uri"])) } else { kv_put(env, &bcat(&[b"coin:", symbol, b":uri"]), uri) }
+}
+
+pub fn call_set_metadata(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+
+    if !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+    if !has_permission(env, symbol, &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
+
+    put_metadata(env, symbol, &args[1], &args[2]);
+}
+
+//coins created before decimals were persisted use the chain default
+pub fn decimals(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8]) -> u32 {
+    match kv_get(env, &bcat(&[b"coin:", symbol, b":decimals"])) {
+        Some(decimals) => std::str::from_utf8(&decimals).unwrap().parse::<u32>().unwrap_or_else(|_| panic_any("invalid_decimals")),
+        None => DECIMALS
+    }
+}
+
+//%{symbol, total_supply, max_supply, decimals, name, uri, owner, mintable, pausable, paused, soulbound, allowlist_mode}
+pub fn view_coin(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    if symbol == b"AMA" { return view_coin_ama(env) }
+    if !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+
+    let mut pairs = vec![
+        (Term::Binary(b"symbol".to_vec()), Term::Binary(symbol.to_vec())),
+        (Term::Binary(b"total_supply".to_vec()), Term::VarInt(total_supply(env, symbol))),
+        (Term::Binary(b"decimals".to_vec()), Term::VarInt(decimals(env, symbol) as i128)),
+        (Term::Binary(b"name".to_vec()), Term::Binary(kv_get(env, &bcat(&[b"coin:", symbol, b":name"])).unwrap_or_default())),
+        (Term::Binary(b"uri".to_vec()), Term::Binary(kv_get(env, &bcat(&[b"coin:", symbol, b":uri"])).unwrap_or_default())),
+        (Term::Binary(b"mintable".to_vec()), Term::Bool(mintable(env, symbol))),
+        (Term::Binary(b"pausable".to_vec()), Term::Bool(pausable(env, symbol))),
+        (Term::Binary(b"paused".to_vec()), Term::Bool(paused(env, symbol))),
+        (Term::Binary(b"soulbound".to_vec()), Term::Bool(soulbound(env, symbol))),
+        (Term::Binary(b"allowlist_mode".to_vec()), Term::Bool(allowlist_mode(env, symbol))),
+    ];
+    if let Some(max_supply) = max_supply(env, symbol) {
+        pairs.push((Term::Binary(b"max_supply".to_vec()), Term::VarInt(max_supply)));
+    }
+    if let Some(owner) = owner(env, symbol) {
+        pairs.push((Term::Binary(b"owner".to_vec()), Term::Binary(owner)));
+    }
+    encode(Term::PropList(pairs))
+}
+
+//AMA has no coin:AMA:* keys, its supply is everything emitted up to this epoch minus what sits at the burn address
+fn view_coin_ama(env: &mut crate::consensus::consensus_apply::ApplyEnv) -> Vec<u8> {
+    let emitted = consensus::bic::epoch::circulating_without_burn(env.caller_env.entry_epoch);
+    let burnt = balance_burnt(env, b"AMA");
+    encode(Term::PropList(vec![
+        (Term::Binary(b"symbol".to_vec()), Term::Binary(b"AMA".to_vec())),
+        (Term::Binary(b"total_supply".to_vec()), Term::VarInt(emitted - burnt)),
+        (Term::Binary(b"decimals".to_vec()), Term::VarInt(DECIMALS as i128)),
+        (Term::Binary(b"name".to_vec()), Term::Binary(b"Amadeus".to_vec())),
+        (Term::Binary(b"uri".to_vec()), Term::Binary(Vec::new())),
+        (Term::Binary(b"mintable".to_vec()), Term::Bool(false)),
+        (Term::Binary(b"pausable".to_vec()), Term::Bool(false)),
+        (Term::Binary(b"paused".to_vec()), Term::Bool(false)),
+        (Term::Binary(b"soulbound".to_vec()), Term::Bool(false)),
+        (Term::Binary(b"allowlist_mode".to_vec()), Term::Bool(false)),
+    ]))
 }
 
 pub fn call_mint(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
@@ -159,7 +500,7 @@ pub fn call_mint(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Ve
     let symbol = args[2].as_slice();
     if receiver.len() != 48 { panic_any("invalid_receiver_pk") }
 
-    if !has_permission(env, &symbol, &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
+    if !has_role(env, &symbol, b"minter", &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
 
     mint(env, receiver, amount, symbol);
 }
@@ -171,11 +512,110 @@ pub fn mint(env: &mut crate::consensus::consensus_apply::ApplyEnv, receiver: &[u
     if !exists(env, &symbol) { panic_any("symbol_doesnt_exist") }
     if !mintable(env, &symbol) { panic_any("not_mintable") }
     if paused(env, &symbol) { panic_any("paused") }
+    check_account(env, symbol, receiver);
+    if let Some(max_supply) = max_supply(env, symbol) {
+        if total_supply(env, symbol).saturating_add(amount) > max_supply { panic_any("exceeds_max_supply") }
+    }
 
     kv_increment(env, &bcat(&[b"account:", receiver, b":balance:", symbol]), amount);
     kv_increment(env, &bcat(&[b"coin:", symbol, b":totalSupply"]), amount);
 }
 
+//AMA burns stay transfers to the burn address so emission accounting keeps working
+pub fn call_burn(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 2 { panic_any("invalid_args") }
+    let amount = std::str::from_utf8(&args[0]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    let symbol = args[1].as_slice();
+    let owner = env.caller_env.account_caller.clone();
+
+    if symbol == b"AMA" { return transfer(env, &owner, &BURN_ADDRESS, amount, symbol) }
+    burn(env, &owner, amount, symbol);
+}
+
+pub fn burn(env: &mut crate::consensus::consensus_apply::ApplyEnv, owner: &[u8], amount: i128, symbol: &[u8]) {
+    if amount <= 0 { panic_any("invalid_amount") }
+    if !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+    if paused(env, symbol) { panic_any("paused") }
+    if frozen(env, symbol, owner) { panic_any("account_frozen") }
+    if amount > balance(env, owner, symbol) { panic_any("insufficient_funds") }
+
+    kv_increment(env, &bcat(&[b"account:", owner, b":balance:", symbol]), -amount);
+    kv_increment(env, &bcat(&[b"coin:", symbol, b":totalSupply"]), -amount);
+}
+
+fn check_compliance_change(env: &mut crate::consensus::consensus_apply::ApplyEnv, symbol: &[u8], role: &[u8]) {
+    if !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+    if !pausable(env, symbol) { panic_any("not_pausable") }
+    if !has_role(env, symbol, role, &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
+}
+
+fn parse_direction(direction: &[u8]) -> bool {
+    match direction {
+        b"true" => true,
+        b"false" => false,
+        _ => panic_any("invalid_direction")
+    }
+}
+
+//symbol, account, true|false
+pub fn call_freeze(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    let account = args[1].as_slice();
+    let freeze = parse_direction(&args[2]);
+    if account.len() != 48 { panic_any("invalid_account_pk") }
+    check_compliance_change(env, symbol, b"pauser");
+
+    let key = bcat(&[b"coin:", symbol, b":frozen:", account]);
+    if freeze { kv_put(env, &key, b"true") } else { kv_delete(env, &key) }
+    let caller = env.caller_env.account_caller.clone();
+    let topic: &[u8] = if freeze { b"account_frozen" } else { b"account_unfrozen" };
+    consensus::bic::emit_event(env, b"Coin", vec![topic.to_vec(), symbol.to_vec(), account.to_vec()], caller);
+}
+
+//symbol, true|false, when on only allowlisted accounts can send or receive
+pub fn call_set_allowlist_mode(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 2 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    let enabled = parse_direction(&args[1]);
+    check_compliance_change(env, symbol, b"admin");
+
+    let key = bcat(&[b"coin:", symbol, b":allowlist_mode"]);
+    if enabled { kv_put(env, &key, b"true") } else { kv_delete(env, &key) }
+    let caller = env.caller_env.account_caller.clone();
+    consensus::bic::emit_event(env, b"Coin", vec![b"allowlist_mode".to_vec(), symbol.to_vec(), args[1].clone()], caller);
+}
+
+//symbol, account, true|false
+pub fn call_set_allowlisted(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+    let account = args[1].as_slice();
+    let allowed = parse_direction(&args[2]);
+    if account.len() != 48 { panic_any("invalid_account_pk") }
+    check_compliance_change(env, symbol, b"admin");
+
+    let key = bcat(&[b"coin:", symbol, b":allowlist:", account]);
+    if allowed { kv_put(env, &key, b"true") } else { kv_delete(env, &key) }
+    let caller = env.caller_env.account_caller.clone();
+    let topic: &[u8] = if allowed { b"allowlist_added" } else { b"allowlist_removed" };
+    consensus::bic::emit_event(env, b"Coin", vec![topic.to_vec(), symbol.to_vec(), account.to_vec()], caller);
+}
+
+//one way, mintable can never be set again
+pub fn call_disable_mint(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let symbol = args[0].as_slice();
+
+    if !exists(env, symbol) { panic_any("symbol_doesnt_exist") }
+    if !has_permission(env, symbol, &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
+    if !mintable(env, symbol) { panic_any("not_mintable") }
+
+    kv_put(env, &bcat(&[b"coin:", symbol, b":mintable"]), b"false");
+    let caller = env.caller_env.account_caller.clone();
+    consensus::bic::emit_event(env, b"Coin", vec![b"mint_disabled".to_vec(), symbol.to_vec()], caller);
+}
+
 pub fn call_pause(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
     if args.len() != 2 { panic_any("invalid_args") }
     let symbol = args[0].as_slice();
@@ -184,8 +624,140 @@ pub fn call_pause(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: V
     if direction != b"true" && direction != b"false" { panic_any("invalid_direction") }
 
     if !exists(env, &symbol) { panic_any("symbol_doesnt_exist") }
-    if !has_permission(env, &symbol, &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
+    if !has_role(env, &symbol, b"pauser", &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
     if !pausable(env, &symbol) { panic_any("not_pausable") }
 
     kv_put(env, &bcat(&[b"coin:", &symbol, b":paused"]), &direction);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::consensus_apply::{with_test_env, ApplyEnv};
+    use crate::consensus::consensus_apply::test_util::{args, as_caller, err, pk};
+
+    //creates TESTCOIN with 1000 units held by owner
+    fn create(env: &mut ApplyEnv, owner: &[u8]) {
+        as_caller(env, owner);
+        call_create_and_mint(env, args(&[b"TESTCOIN", b"1000"]));
+    }
+
+    #[test]
+    fn allowance_approve_increase_decrease() {
+        with_test_env(true, |env| {
+            let (owner, spender) = (pk(1), pk(2));
+            create(env, &owner);
+
+            call_approve(env, args(&[&spender, b"100", b"TESTCOIN"]));
+            assert_eq!(allowance(env, &owner, &spender, b"TESTCOIN"), 100);
+            call_increase_allowance(env, args(&[&spender, b"50", b"TESTCOIN"]));
+            assert_eq!(allowance(env, &owner, &spender, b"TESTCOIN"), 150);
+            call_decrease_allowance(env, args(&[&spender, b"150", b"TESTCOIN"]));
+            assert_eq!(allowance(env, &owner, &spender, b"TESTCOIN"), 0);
+
+            assert_eq!(err(|| call_decrease_allowance(env, args(&[&spender, b"1", b"TESTCOIN"]))), "allowance_below_zero");
+            assert_eq!(err(|| call_approve(env, args(&[&owner, b"1", b"TESTCOIN"]))), "spender_is_owner");
+            assert_eq!(err(|| call_approve(env, args(&[&spender, b"-1", b"TESTCOIN"]))), "invalid_amount");
+        });
+    }
+
+    #[test]
+    fn allowance_transfer_from_spends_and_moves() {
+        with_test_env(true, |env| {
+            let (owner, spender, receiver) = (pk(1), pk(2), pk(3));
+            create(env, &owner);
+            call_approve(env, args(&[&spender, b"100", b"TESTCOIN"]));
+
+            as_caller(env, &spender);
+            call_transfer_from(env, args(&[&owner, &receiver, b"60", b"TESTCOIN"]));
+            assert_eq!(allowance(env, &owner, &spender, b"TESTCOIN"), 40);
+            assert_eq!(balance(env, &owner, b"TESTCOIN"), 940);
+            assert_eq!(balance(env, &receiver, b"TESTCOIN"), 60);
+            //the spender itself never holds the tokens
+            assert_eq!(balance(env, &spender, b"TESTCOIN"), 0);
+
+            assert_eq!(err(|| call_transfer_from(env, args(&[&owner, &receiver, b"41", b"TESTCOIN"]))), "insufficient_allowance");
+            as_caller(env, &receiver);
+            assert_eq!(err(|| call_transfer_from(env, args(&[&owner, &receiver, b"1", b"TESTCOIN"]))), "insufficient_allowance");
+        });
+    }
+
+    #[test]
+    fn roles_grant_and_revoke() {
+        with_test_env(true, |env| {
+            let (owner, admin, minter) = (pk(1), pk(2), pk(3));
+            create(env, &owner);
+            assert!(has_role(env, b"TESTCOIN", b"minter", &owner));
+
+            call_grant_role(env, args(&[b"TESTCOIN", b"admin", &admin]));
+            as_caller(env, &admin);
+            call_grant_role(env, args(&[b"TESTCOIN", b"minter", &minter]));
+            assert!(has_role(env, b"TESTCOIN", b"minter", &minter));
+            assert!(!has_role(env, b"TESTCOIN", b"admin", &minter));
+            assert!(!has_role(env, b"TESTCOIN", b"pauser", &minter));
+            assert_eq!(err(|| call_grant_role(env, args(&[b"TESTCOIN", b"minter", &minter]))), "role_already_granted");
+
+            //only the owner manages admins and the owner itself cannot be revoked
+            assert_eq!(err(|| call_grant_role(env, args(&[b"TESTCOIN", b"admin", &minter]))), "no_permissions");
+            assert_eq!(err(|| call_revoke_role(env, args(&[b"TESTCOIN", b"admin", &owner]))), "no_permissions");
+            as_caller(env, &owner);
+            assert_eq!(err(|| call_revoke_role(env, args(&[b"TESTCOIN", b"admin", &owner]))), "cannot_revoke_owner");
+
+            call_revoke_role(env, args(&[b"TESTCOIN", b"minter", &minter]));
+            assert!(!has_role(env, b"TESTCOIN", b"minter", &minter));
+            assert_eq!(err(|| call_revoke_role(env, args(&[b"TESTCOIN", b"minter", &minter]))), "role_not_granted");
+
+            as_caller(env, &minter);
+            assert_eq!(err(|| call_grant_role(env, args(&[b"TESTCOIN", b"minter", &minter]))), "no_permissions");
+        });
+    }
+
+    #[test]
+    fn transfer_ownership_revokes_previous_admin() {
+        with_test_env(true, |env| {
+            let (first, second, third) = (pk(1), pk(2), pk(3));
+            create(env, &first);
+            assert_eq!(err(|| call_transfer_ownership(env, args(&[b"TESTCOIN", &second, b"yes"]))), "invalid_keep_admin");
+
+            env.events.clear();
+            call_transfer_ownership(env, args(&[b"TESTCOIN", &second]));
+            assert_eq!(owner(env, b"TESTCOIN"), Some(second.clone()));
+            assert!(has_role(env, b"TESTCOIN", b"admin", &second));
+            assert!(!has_role(env, b"TESTCOIN", b"admin", &first));
+            let topics: Vec<Vec<Vec<u8>>> = env.events.iter().map(|e| e.topics.clone()).collect();
+            assert_eq!(topics, vec![
+                args(&[b"role_revoked", b"TESTCOIN", b"admin", &first]),
+                args(&[b"ownership_transferred", b"TESTCOIN", &first, &second]),
+            ]);
+
+            //keeping the admin role is opt-in
+            as_caller(env, &second);
+            call_transfer_ownership(env, args(&[b"TESTCOIN", &third, b"true"]));
+            assert_eq!(owner(env, b"TESTCOIN"), Some(third.clone()));
+            assert!(has_role(env, b"TESTCOIN", b"admin", &second));
+            assert_eq!(err(|| call_transfer_ownership(env, args(&[b"TESTCOIN", &first]))), "not_owner");
+        });
+    }
+
+    #[test]
+    fn roles_legacy_owner_fallback() {
+        with_test_env(true, |env| {
+            let (creator, second) = (pk(1), pk(2));
+            create(env, &creator);
+            //coins created before ownership only have the admin list
+            let admins = encode(Term::List(vec![Term::Binary(creator.clone()), Term::Binary(second.clone())]));
+            kv_put(env, b"coin:TESTCOIN:permission", &admins);
+            kv_delete(env, b"coin:TESTCOIN:owner");
+            assert_eq!(owner(env, b"TESTCOIN"), Some(creator.clone()));
+
+            as_caller(env, &second);
+            assert_eq!(err(|| call_renounce_ownership(env, args(&[b"TESTCOIN"]))), "not_owner");
+            as_caller(env, &creator);
+            call_renounce_ownership(env, args(&[b"TESTCOIN"]));
+            //renouncing must not hand ownership to the next legacy admin
+            assert_eq!(owner(env, b"TESTCOIN"), None);
+            assert!(has_role(env, b"TESTCOIN", b"admin", &second));
+            assert!(!has_role(env, b"TESTCOIN", b"admin", &creator));
+        });
+    }
+}
@@ -1,31 +1,212 @@
 use std::panic::panic_any;
-use crate::consensus::consensus_kv::{kv_get, kv_put};
+use crate::consensus::consensus_kv::{kv_get, kv_put, kv_increment};
 use crate::consensus::bic::wasm::{validate_contract};
 use crate::{bcat};
+use vecpak::{encode, Term};
 
 pub fn call_deploy(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
     if args.len() == 0 { panic_any("invalid_args") }
     let wasmbytes = args[0].as_slice();
+    let account = env.caller_env.account_caller.clone();
+
+    //only reachable on testnet (see call_bic), mainnet never had redeploys to preserve
+    if bytecode(env, &account).is_some() { panic_any("contract_already_deployed") }
+
     validate_contract(env, wasmbytes);
-    kv_put(env, &bcat(&[b"account:", &env.caller_env.account_caller, b":attribute:bytecode"]), wasmbytes);
+    put_bytecode(env, &account, wasmbytes);
+    kv_put(env, &bcat(&[b"account:", &account, b":attribute:upgrade_authority"]), &account);
+
     if args.len() >= 2 {
-        let og_account_current = env.caller_env.account_current.clone();
-        let og_account_caller = env.caller_env.account_caller.clone();
+        call_init(env, &account, args[1].as_slice().to_vec(), args[2..].to_vec());
+    }
+}
+
+pub fn call_upgrade(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() < 2 { panic_any("invalid_args") }
+    let contract = args[0].as_slice();
+    let wasmbytes = args[1].as_slice();
 
-        env.caller_env.account_current = og_account_caller.clone();
-        env.caller_env.account_caller = og_account_current.clone();
-        env.caller_env.call_counter += 1;
-        env.caller_env.call_return_value = Vec::new();
+    if bytecode(env, contract).is_none() { panic_any("account_has_no_bytecode") }
+    if frozen(env, contract) { panic_any("contract_frozen") }
+    if upgrade_authority(env, contract) != env.caller_env.account_caller { panic_any("no_permissions") }
 
-        let init_function = args[1].as_slice().to_vec();
-        let init_args = args[2..].to_vec();
-        crate::consensus::consensus_apply::call_wasmvm(env, og_account_caller.clone(), init_function, init_args, None, None);
+    validate_contract(env, wasmbytes);
+    put_bytecode(env, contract, wasmbytes);
 
-        env.caller_env.account_current = og_account_current;
-        env.caller_env.account_caller = og_account_caller;
+    if args.len() >= 3 {
+        call_init(env, contract, args[2].as_slice().to_vec(), args[3..].to_vec());
     }
 }
 
+pub fn call_freeze(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let contract = args[0].as_slice();
+
+    if bytecode(env, contract).is_none() { panic_any("account_has_no_bytecode") }
+    if frozen(env, contract) { panic_any("contract_frozen") }
+    if upgrade_authority(env, contract) != env.caller_env.account_caller { panic_any("no_permissions") }
+
+    kv_put(env, &bcat(&[b"account:", contract, b":attribute:frozen"]), b"true");
+}
+
+pub fn call_set_upgrade_authority(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 2 { panic_any("invalid_args") }
+    let contract = args[0].as_slice();
+    let new_authority = args[1].as_slice();
+
+    if !crate::consensus::bls12_381::validate_public_key(new_authority) { panic_any("invalid_authority_pk") }
+    if bytecode(env, contract).is_none() { panic_any("account_has_no_bytecode") }
+    if frozen(env, contract) { panic_any("contract_frozen") }
+    if upgrade_authority(env, contract) != env.caller_env.account_caller { panic_any("no_permissions") }
+
+    kv_put(env, &bcat(&[b"account:", contract, b":attribute:upgrade_authority"]), new_authority);
+}
+
+pub fn view_metadata(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let contract = args[0].as_slice();
+
+    let code = bytecode(env, contract).unwrap_or_else(|| panic_any("account_has_no_bytecode"));
+    //contracts deployed before code hashes were tracked
+    let code_hash = kv_get(env, &bcat(&[b"account:", contract, b":attribute:code_hash"]))
+        .unwrap_or_else(|| blake3::hash(&code).as_bytes().to_vec());
+    let frozen = frozen(env, contract);
+    let authority = upgrade_authority(env, contract);
+
+    encode(Term::PropList(vec![
+        (Term::Binary(b"code_hash".to_vec()), Term::Binary(code_hash)),
+        (Term::Binary(b"code_size".to_vec()), Term::VarInt(code.len() as i128)),
+        (Term::Binary(b"code_version".to_vec()), Term::VarInt(code_version(env, contract) as i128)),
+        (Term::Binary(b"upgrade_authority".to_vec()), Term::Binary(authority)),
+        (Term::Binary(b"frozen".to_vec()), Term::Bool(frozen)),
+    ]))
+}
+
+fn call_init(env: &mut crate::consensus::consensus_apply::ApplyEnv, contract: &[u8], function: Vec<u8>, args: Vec<Vec<u8>>) {
+    let og_account_current = env.caller_env.account_current.clone();
+    let og_account_caller = env.caller_env.account_caller.clone();
+
+    env.caller_env.account_current = contract.to_vec();
+    env.caller_env.account_caller = og_account_current.clone();
+    env.caller_env.call_counter += 1;
+    env.caller_env.call_return_value = Vec::new();
+
+    crate::consensus::consensus_apply::call_wasmvm(env, contract.to_vec(), function, args, None, None);
+
+    env.caller_env.account_current = og_account_current;
+    env.caller_env.account_caller = og_account_caller;
+}
+
+fn put_bytecode(env: &mut crate::consensus::consensus_apply::ApplyEnv, account: &[u8], wasmbytes: &[u8]) {
+    kv_put(env, &bcat(&[b"account:", account, b":attribute:bytecode"]), wasmbytes);
+    kv_put(env, &bcat(&[b"account:", account, b":attribute:code_hash"]), blake3::hash(wasmbytes).as_bytes());
+    kv_increment(env, &bcat(&[b"account:", account, b":attribute:code_version"]), 1);
+    crate::consensus::bic::abi::put(env, account, wasmbytes);
+}
+
 pub fn bytecode(env: &mut crate::consensus::consensus_apply::ApplyEnv, account: &[u8]) -> Option<Vec<u8>> {
     kv_get(env, &bcat(&[b"account:", &account, b":attribute:bytecode"]))
 }
+
+pub fn code_version(env: &mut crate::consensus::consensus_apply::ApplyEnv, account: &[u8]) -> u64 {
+    match kv_get(env, &bcat(&[b"account:", account, b":attribute:code_version"])) {
+        Some(version) => std::str::from_utf8(&version).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_code_version")),
+        None => 0
+    }
+}
+
+pub fn frozen(env: &mut crate::consensus::consensus_apply::ApplyEnv, account: &[u8]) -> bool {
+    match kv_get(env, &bcat(&[b"account:", account, b":attribute:frozen"])).as_deref() {
+        Some(b"true") => true,
+        _ => false
+    }
+}
+
+pub fn upgrade_authority(env: &mut crate::consensus::consensus_apply::ApplyEnv, account: &[u8]) -> Vec<u8> {
+    //defaults to the contract account itself
+    kv_get(env, &bcat(&[b"account:", account, b":attribute:upgrade_authority"])).unwrap_or_else(|| account.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::consensus_apply::{call_bic, with_test_env};
+    use crate::consensus::consensus_apply::test_util::{args, as_caller, err, pk, TICK_BOOM_WASM};
+
+    #[test]
+    fn deploy_once() {
+        with_test_env(true, |env| {
+            let c = pk(1);
+            as_caller(env, &c);
+            call_deploy(env, args(&[TICK_BOOM_WASM]));
+            assert_eq!(bytecode(env, &c).as_deref(), Some(TICK_BOOM_WASM));
+            assert_eq!(code_version(env, &c), 1);
+            assert_eq!(upgrade_authority(env, &c), c);
+            assert_eq!(err(|| call_deploy(env, args(&[TICK_BOOM_WASM]))), "contract_already_deployed");
+            assert_eq!(code_version(env, &c), 1);
+        });
+    }
+
+    #[test]
+    fn deploy_not_dispatched_on_mainnet() {
+        with_test_env(false, |env| {
+            as_caller(env, &pk(1));
+            assert_eq!(err(|| call_bic(env, b"Contract".to_vec(), b"deploy".to_vec(), args(&[TICK_BOOM_WASM]), None, None)), "invalid_bic_action");
+        });
+    }
+
+    #[test]
+    fn upgrade_by_authority_only() {
+        with_test_env(true, |env| {
+            let (c, other) = (pk(1), pk(2));
+            as_caller(env, &c);
+            call_deploy(env, args(&[TICK_BOOM_WASM]));
+
+            as_caller(env, &other);
+            assert_eq!(err(|| call_upgrade(env, args(&[&c, TICK_BOOM_WASM]))), "no_permissions");
+            assert_eq!(err(|| call_upgrade(env, args(&[&other, TICK_BOOM_WASM]))), "account_has_no_bytecode");
+
+            as_caller(env, &c);
+            call_upgrade(env, args(&[&c, TICK_BOOM_WASM]));
+            assert_eq!(code_version(env, &c), 2);
+        });
+    }
+
+    #[test]
+    fn upgrade_authority_handover() {
+        with_test_env(true, |env| {
+            let (c, next) = (pk(1), pk(2));
+            as_caller(env, &c);
+            call_deploy(env, args(&[TICK_BOOM_WASM]));
+            assert_eq!(err(|| call_set_upgrade_authority(env, args(&[&c, &[1u8; 48]]))), "invalid_authority_pk");
+            call_set_upgrade_authority(env, args(&[&c, &next]));
+            assert_eq!(upgrade_authority(env, &c), next);
+
+            //the contract account itself no longer has a say
+            assert_eq!(err(|| call_upgrade(env, args(&[&c, TICK_BOOM_WASM]))), "no_permissions");
+            as_caller(env, &next);
+            call_upgrade(env, args(&[&c, TICK_BOOM_WASM]));
+            assert_eq!(code_version(env, &c), 2);
+        });
+    }
+
+    #[test]
+    fn freeze_is_final() {
+        with_test_env(true, |env| {
+            let (c, other) = (pk(1), pk(2));
+            as_caller(env, &c);
+            call_deploy(env, args(&[TICK_BOOM_WASM]));
+
+            as_caller(env, &other);
+            assert_eq!(err(|| call_freeze(env, args(&[&c]))), "no_permissions");
+            as_caller(env, &c);
+            call_freeze(env, args(&[&c]));
+            assert!(frozen(env, &c));
+
+            assert_eq!(err(|| call_upgrade(env, args(&[&c, TICK_BOOM_WASM]))), "contract_frozen");
+            assert_eq!(err(|| call_set_upgrade_authority(env, args(&[&c, &other]))), "contract_frozen");
+            assert_eq!(err(|| call_freeze(env, args(&[&c]))), "contract_frozen");
+            assert_eq!(code_version(env, &c), 1);
+        });
+    }
+}
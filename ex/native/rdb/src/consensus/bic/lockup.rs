@@ -1,8 +1,10 @@
 use std::panic::panic_any;
 use crate::consensus::consensus_apply::ApplyEnv;
 use crate::{bcat};
-use crate::consensus::{bic::{coin::BURN_ADDRESS, coin::balance}};
-use crate::consensus::{consensus_kv::{kv_get, kv_increment, kv_put, kv_delete}};
+use crate::consensus::{bic::{protocol, coin::BURN_ADDRESS, coin::balance, coin::paused, coin::soulbound, coin::check_account}};
+use crate::consensus::{consensus_kv::{kv_get, kv_get_next, kv_increment, kv_put, kv_delete}};
+use crate::model::_codec::{pl_find_opt, pl_get_bytes_opt};
+use vecpak::{encode, decode, Term};
 
 pub fn create_lock(env: &mut ApplyEnv, receiver: &[u8], amount: i128, symbol: &[u8], unlock_height: u64) {
     if amount <= 0 { panic_any("invalid_amount") }
@@ -37,6 +39,17 @@ pub fn call_lock(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Ve
     create_lock(env, receiver, amount, symbol, unlock_height)
 }
 
+//legacy lock vaults are unlock_height-amount-symbol strings
+pub fn decode_lock(vault: &[u8]) -> (u64, i128, Vec<u8>) {
+    let vault_parts: Vec<Vec<u8>> = vault.split(|&b| b == b'-').map(|seg| seg.to_vec()).collect();
+    let unlock_height = vault_parts[0].as_slice();
+    let unlock_height = std::str::from_utf8(&unlock_height).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_unlock_height"));
+    let amount = vault_parts[1].as_slice();
+    let amount = std::str::from_utf8(&amount).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_unlock_amount"));
+    let symbol = vault_parts.get(2).cloned().unwrap_or_else(|| panic_any("invalid_vault"));
+    (unlock_height, amount, symbol)
+}
+
 pub fn call_unlock(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
     if args.len() != 1 { panic_any("invalid_args") }
     let vault_index = args[0].as_slice();
@@ -46,18 +59,300 @@ pub fn call_unlock(env: &mut crate::consensus::consensus_apply::ApplyEnv, args:
     let vault = kv_get(env, vault_key);
     if vault.is_none() { panic_any("invalid_vault") }
     let vault = vault.unwrap();
+    if decode_vesting(&vault).is_some() { panic_any("vesting_vault_use_claim") }
 
-    let vault_parts: Vec<Vec<u8>> = vault.split(|&b| b == b'-').map(|seg| seg.to_vec()).collect();
-    let unlock_height = vault_parts[0].as_slice();
-    let unlock_height = std::str::from_utf8(&unlock_height).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_unlock_height"));
-    let amount = vault_parts[1].as_slice();
-    let amount = std::str::from_utf8(&amount).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_unlock_amount"));
-    let symbol = vault_parts[2].as_slice();
+    let (unlock_height, amount, symbol) = decode_lock(&vault);
 
     if env.caller_env.entry_height < unlock_height {
         panic_any("vault_is_locked")
     } else {
-        kv_increment(env, &bcat(&[b"account:", &env.caller_env.account_caller, b":balance:", symbol]), amount);
+        kv_increment(env, &bcat(&[b"account:", &env.caller_env.account_caller, b":balance:", &symbol]), amount);
         kv_delete(env, vault_key);
     }
 }
+
+//vesting vaults share the vault keyspace but are vecpak maps
+//nothing is claimable before the cliff, then it releases linearly from start to end
+pub struct VestingVault {
+    pub symbol: Vec<u8>,
+    pub amount: i128,
+    pub released: i128,
+    pub start_height: u64,
+    pub cliff_height: u64,
+    pub end_height: u64,
+}
+
+impl VestingVault {
+    pub fn vested(&self, height: u64) -> i128 {
+        if height < self.cliff_height { return 0 }
+        if height >= self.end_height { return self.amount }
+        let elapsed = (height - self.start_height) as i128;
+        let total = (self.end_height - self.start_height) as i128;
+        self.amount.saturating_mul(elapsed) / total
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        encode(Term::PropList(vec![
+            (Term::Binary(b"symbol".to_vec()), Term::Binary(self.symbol.clone())),
+            (Term::Binary(b"amount".to_vec()), Term::VarInt(self.amount)),
+            (Term::Binary(b"released".to_vec()), Term::VarInt(self.released)),
+            (Term::Binary(b"start_height".to_vec()), Term::VarInt(self.start_height as i128)),
+            (Term::Binary(b"cliff_height".to_vec()), Term::VarInt(self.cliff_height as i128)),
+            (Term::Binary(b"end_height".to_vec()), Term::VarInt(self.end_height as i128)),
+        ]))
+    }
+}
+
+pub fn decode_vesting(vault: &[u8]) -> Option<VestingVault> {
+    let Ok(Term::PropList(pairs)) = decode(vault) else { return None };
+    let int = |key: &[u8]| match pl_find_opt(&pairs, key) {
+        Some(Term::VarInt(v)) => *v,
+        _ => panic_any("invalid_vault")
+    };
+    Some(VestingVault {
+        symbol: pl_get_bytes_opt(&pairs, b"symbol").unwrap_or_else(|| panic_any("invalid_vault")).to_vec(),
+        amount: int(b"amount"),
+        released: int(b"released"),
+        start_height: int(b"start_height") as u64,
+        cliff_height: int(b"cliff_height") as u64,
+        end_height: int(b"end_height") as u64,
+    })
+}
+
+//receiver, amount, symbol, cliff, duration with heights counted from this entry
+pub fn call_vest(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 5 { panic_any("invalid_args") }
+    let receiver = args[0].as_slice();
+    let amount = std::str::from_utf8(&args[1]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    let symbol = args[2].as_slice();
+    let cliff = std::str::from_utf8(&args[3]).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_cliff"));
+    let duration = std::str::from_utf8(&args[4]).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_duration"));
+
+    if receiver.len() != 48 || !crate::consensus::bls12_381::validate_public_key(receiver) { panic_any("invalid_receiver_pk") }
+    if amount <= 0 { panic_any("invalid_amount") }
+    if duration == 0 { panic_any("invalid_duration") }
+    if cliff > duration { panic_any("cliff_after_end") }
+
+    let sender = env.caller_env.account_caller.clone();
+    if amount > balance(env, &sender, symbol) { panic_any("insufficient_funds") }
+    //vesting moves coins like a transfer so it obeys the same restrictions
+    if paused(env, symbol) { panic_any("paused") }
+    if soulbound(env, symbol) { panic_any("soulbound") }
+    check_account(env, symbol, &sender);
+    check_account(env, symbol, receiver);
+    kv_increment(env, &bcat(&[b"account:", &sender, b":balance:", symbol]), -amount);
+
+    let start_height = env.caller_env.entry_height;
+    let vault = VestingVault {
+        symbol: symbol.to_vec(),
+        amount,
+        released: 0,
+        start_height,
+        cliff_height: start_height.saturating_add(cliff),
+        end_height: start_height.saturating_add(duration),
+    };
+    let vault_index = kv_increment(env, &bcat(&[b"bic:lockup:unique_index"]), 1);
+    kv_put(env, &bcat(&[b"bic:lockup:vault:", receiver, b":", vault_index.to_string().as_bytes()]), &vault.encode());
+}
+
+//releases everything vested so far, the vault is removed once fully released
+pub fn call_claim(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let vault_index = args[0].as_slice();
+    let owner = env.caller_env.account_caller.clone();
+    let vault_key = bcat(&[b"bic:lockup:vault:", &owner, b":", vault_index]);
+
+    let vault = kv_get(env, &vault_key).unwrap_or_else(|| panic_any("invalid_vault"));
+    let mut vault = decode_vesting(&vault).unwrap_or_else(|| panic_any("not_a_vesting_vault"));
+
+    let claimable = vault.vested(env.caller_env.entry_height) - vault.released;
+    if claimable <= 0 { panic_any("nothing_to_claim") }
+    if paused(env, &vault.symbol) { panic_any("paused") }
+    if soulbound(env, &vault.symbol) { panic_any("soulbound") }
+    check_account(env, &vault.symbol, &owner);
+
+    kv_increment(env, &bcat(&[b"account:", &owner, b":balance:", &vault.symbol]), claimable);
+    vault.released += claimable;
+    if vault.released >= vault.amount {
+        kv_delete(env, &vault_key);
+    } else {
+        kv_put(env, &vault_key, &vault.encode());
+    }
+}
+
+//pk or pk, cursor, limit; an empty cursor starts from the first vault and an empty limit means the max
+pub fn vault_page_args(args: &[Vec<u8>]) -> (Vec<u8>, usize) {
+    match args.len() {
+        1 => (Vec::new(), protocol::VIEW_VAULTS_MAX_ITEMS),
+        3 => {
+            let limit = if args[2].is_empty() { protocol::VIEW_VAULTS_MAX_ITEMS } else {
+                std::str::from_utf8(&args[2]).ok().and_then(|s| s.parse::<usize>().ok()).unwrap_or_else(|| panic_any("invalid_limit"))
+            };
+            if limit == 0 || limit > protocol::VIEW_VAULTS_MAX_ITEMS { panic_any("invalid_limit") }
+            (args[1].clone(), limit)
+        }
+        _ => panic_any("invalid_args")
+    }
+}
+
+//symbol, amount still locked
+fn vault_locked(val: &[u8]) -> (Vec<u8>, i128) {
+    match decode_vesting(val) {
+        Some(vault) => (vault.symbol, vault.amount - vault.released),
+        None => {
+            let (_unlock_height, amount, symbol) = decode_lock(val);
+            (symbol, amount)
+        }
+    }
+}
+
+//pk, [cursor, limit] -> %{vaults: [%{index, kind, symbol, amount, unlock_height, ...}], cursor}
+//vesting vaults add released, vested, start_height and cliff_height, their unlock_height is the end
+//vaults come in key order, so indices sort as strings ("10" before "2"); totals are in view_vault_totals
+//cursor is the last index when the page is full, pass it back for the next page, empty once done
+pub fn view_vaults(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    let (mut cursor, limit) = vault_page_args(args);
+    let prefix = bcat(&[b"bic:lockup:vault:", &args[0], b":"]);
+    let height = env.caller_env.entry_height;
+
+    let mut vaults = Vec::new();
+    while vaults.len() < limit {
+        let Some((index, val)) = kv_get_next(env, &prefix, &cursor) else { cursor.clear(); break };
+        let (symbol, fields) = match decode_vesting(&val) {
+            Some(vault) => (vault.symbol.clone(), vec![
+                (Term::Binary(b"kind".to_vec()), Term::Binary(b"vesting".to_vec())),
+                (Term::Binary(b"amount".to_vec()), Term::VarInt(vault.amount)),
+                (Term::Binary(b"unlock_height".to_vec()), Term::VarInt(vault.end_height as i128)),
+                (Term::Binary(b"released".to_vec()), Term::VarInt(vault.released)),
+                (Term::Binary(b"vested".to_vec()), Term::VarInt(vault.vested(height))),
+                (Term::Binary(b"start_height".to_vec()), Term::VarInt(vault.start_height as i128)),
+                (Term::Binary(b"cliff_height".to_vec()), Term::VarInt(vault.cliff_height as i128)),
+            ]),
+            None => {
+                let (unlock_height, amount, symbol) = decode_lock(&val);
+                (symbol, vec![
+                    (Term::Binary(b"kind".to_vec()), Term::Binary(b"lock".to_vec())),
+                    (Term::Binary(b"amount".to_vec()), Term::VarInt(amount)),
+                    (Term::Binary(b"unlock_height".to_vec()), Term::VarInt(unlock_height as i128)),
+                ])
+            }
+        };
+
+        let mut pairs = vec![
+            (Term::Binary(b"index".to_vec()), Term::Binary(index.clone())),
+            (Term::Binary(b"symbol".to_vec()), Term::Binary(symbol)),
+        ];
+        pairs.extend(fields);
+        vaults.push(Term::PropList(pairs));
+        cursor = index;
+    }
+
+    encode(Term::PropList(vec![
+        (Term::Binary(b"vaults".to_vec()), Term::List(vaults)),
+        (Term::Binary(b"cursor".to_vec()), Term::Binary(cursor)),
+    ]))
+}
+
+//pk -> %{symbol => still locked} over every vault of pk, up to VIEW_VAULTS_MAX_SCAN vaults
+pub fn view_vault_totals(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let prefix = bcat(&[b"bic:lockup:vault:", &args[0], b":"]);
+
+    let mut totals: Vec<(Vec<u8>, i128)> = Vec::new();
+    let mut cursor = Vec::new();
+    let mut scanned = 0;
+    while let Some((index, val)) = kv_get_next(env, &prefix, &cursor) {
+        scanned += 1;
+        if scanned > protocol::VIEW_VAULTS_MAX_SCAN { panic_any("too_many_vaults") }
+        let (symbol, locked) = vault_locked(&val);
+        match totals.iter_mut().find(|(s, _)| *s == symbol) {
+            Some((_, total)) => *total += locked,
+            None => totals.push((symbol, locked)),
+        }
+        cursor = index;
+    }
+
+    encode(Term::PropList(totals.into_iter().map(|(symbol, total)| (Term::Binary(symbol), Term::VarInt(total))).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::consensus_apply::with_test_env;
+    use crate::consensus::consensus_apply::test_util::{args, err, pk};
+
+    fn vault(amount: i128, start_height: u64, cliff_height: u64, end_height: u64) -> VestingVault {
+        VestingVault { symbol: b"AMA".to_vec(), amount, released: 0, start_height, cliff_height, end_height }
+    }
+
+    #[test]
+    fn vested_nothing_before_cliff() {
+        let v = vault(1_000, 100, 150, 200);
+        assert_eq!(v.vested(100), 0);
+        assert_eq!(v.vested(149), 0);
+    }
+
+    #[test]
+    fn vested_linear_from_start_at_cliff() {
+        //the cliff releases everything accrued since start, not just from the cliff on
+        let v = vault(1_000, 100, 150, 200);
+        assert_eq!(v.vested(150), 500);
+        assert_eq!(v.vested(175), 750);
+        assert_eq!(v.vested(199), 990);
+    }
+
+    #[test]
+    fn vested_rounds_down_and_caps_at_end() {
+        let v = vault(10, 0, 0, 3);
+        assert_eq!(v.vested(0), 0);
+        assert_eq!(v.vested(1), 3);
+        assert_eq!(v.vested(2), 6);
+        assert_eq!(v.vested(3), 10);
+        assert_eq!(v.vested(u64::MAX), 10);
+    }
+
+    #[test]
+    fn vested_cliff_at_end_releases_all_at_once() {
+        let v = vault(1_000, 100, 200, 200);
+        assert_eq!(v.vested(199), 0);
+        assert_eq!(v.vested(200), 1_000);
+    }
+
+    #[test]
+    fn vesting_encode_decode_roundtrip() {
+        let mut v = vault(1_000, 100, 150, 200);
+        v.released = 250;
+        let d = decode_vesting(&v.encode()).unwrap();
+        assert_eq!((d.symbol, d.amount, d.released), (b"AMA".to_vec(), 1_000, 250));
+        assert_eq!((d.start_height, d.cliff_height, d.end_height), (100, 150, 200));
+    }
+
+    #[test]
+    fn legacy_lock_is_not_vesting() {
+        assert!(decode_vesting(b"200-1000-AMA").is_none());
+    }
+
+    #[test]
+    fn vault_totals_span_every_page() {
+        with_test_env(true, |env| {
+            let owner = pk(1);
+            create_lock(env, &owner, 100, b"AMA", 10);
+            create_lock(env, &owner, 50, b"AMA", 10);
+            create_lock(env, &owner, 7, b"USDC", 10);
+            let mut v = vault(1_000, 0, 0, 200);
+            v.released = 250;
+            kv_put(env, &bcat(&[b"bic:lockup:vault:", &owner, b":99"]), &v.encode());
+
+            let page = decode(&view_vaults(env, &args(&[&owner, b"", b"2"]))).unwrap();
+            let Term::PropList(page) = page else { panic!("not a proplist") };
+            assert!(matches!(pl_find_opt(&page, b"vaults"), Some(Term::List(vaults)) if vaults.len() == 2));
+            assert!(!pl_get_bytes_opt(&page, b"cursor").unwrap().is_empty());
+
+            let totals = decode(&view_vault_totals(env, &args(&[&owner]))).unwrap();
+            let Term::PropList(totals) = totals else { panic!("not a proplist") };
+            assert!(matches!(pl_find_opt(&totals, b"AMA"), Some(Term::VarInt(900))));
+            assert!(matches!(pl_find_opt(&totals, b"USDC"), Some(Term::VarInt(7))));
+            assert_eq!(err(|| { view_vault_totals(env, &args(&[&owner, b""])); }), "invalid_args");
+        });
+    }
+}
@@ -1,6 +1,6 @@
 use std::panic::panic_any;
 use crate::{bcat};
-use crate::consensus::{bic::{coin::{balance, mint, to_flat}, epoch::TREASURY_DONATION_ADDRESS, lockup::{create_lock}}, consensus_kv::{kv_get, kv_get_next, kv_increment, kv_put, kv_delete}};
+use crate::consensus::{bic::{protocol, coin::{balance, mint, to_flat}, epoch::TREASURY_DONATION_ADDRESS, lockup::{create_lock, vault_page_args}}, consensus_kv::{kv_get, kv_get_next, kv_increment, kv_put, kv_delete}};
 use vecpak::{encode, Term};
 
 pub fn call_lock(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
@@ -56,6 +56,69 @@ pub fn call_lock(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Ve
     kv_put(env, &bcat(&[b"bic:lockup_prime:vault:", &env.caller_env.account_caller, b":", vault_index.to_string().as_bytes()]), &vault_value);
 }
 
+//tier-multiplier-unlock_epoch-amount
+pub fn decode_vault(vault: &[u8]) -> (Vec<u8>, u64, u64, u64) {
+    let vault_parts: Vec<Vec<u8>> = vault.split(|&b| b == b'-').map(|seg| seg.to_vec()).collect();
+    if vault_parts.len() < 4 { panic_any("invalid_vault") }
+    let tier = vault_parts[0].clone();
+    let multiplier = std::str::from_utf8(&vault_parts[1]).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_multiplier"));
+    let unlock_epoch = std::str::from_utf8(&vault_parts[2]).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_unlock_epoch"));
+    let unlock_amount = std::str::from_utf8(&vault_parts[3]).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_unlock_amount"));
+    (tier, multiplier, unlock_epoch, unlock_amount)
+}
+
+//pk, [cursor, limit] -> %{vaults: [%{index, tier, multiplier, unlock_epoch, amount, symbol, unlocked}], cursor}
+//paged like Lockup.view_vaults, indices in string order; totals are in view_vault_totals
+pub fn view_vaults(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    let (mut cursor, limit) = vault_page_args(args);
+    let prefix = bcat(&[b"bic:lockup_prime:vault:", &args[0], b":"]);
+
+    let mut vaults = Vec::new();
+    while vaults.len() < limit {
+        let Some((index, val)) = kv_get_next(env, &prefix, &cursor) else { cursor.clear(); break };
+        let (tier, multiplier, unlock_epoch, amount) = decode_vault(&val);
+        vaults.push(Term::PropList(vec![
+            (Term::Binary(b"index".to_vec()), Term::Binary(index.clone())),
+            (Term::Binary(b"tier".to_vec()), Term::Binary(tier)),
+            (Term::Binary(b"multiplier".to_vec()), Term::VarInt(multiplier as i128)),
+            (Term::Binary(b"unlock_epoch".to_vec()), Term::VarInt(unlock_epoch as i128)),
+            (Term::Binary(b"amount".to_vec()), Term::VarInt(amount as i128)),
+            (Term::Binary(b"symbol".to_vec()), Term::Binary(b"AMA".to_vec())),
+            (Term::Binary(b"unlocked".to_vec()), Term::Bool(env.caller_env.entry_epoch >= unlock_epoch)),
+        ]));
+        cursor = index;
+    }
+
+    encode(Term::PropList(vec![
+        (Term::Binary(b"vaults".to_vec()), Term::List(vaults)),
+        (Term::Binary(b"cursor".to_vec()), Term::Binary(cursor)),
+    ]))
+}
+
+//pk -> %{total_amount, total_prime_points} over every vault of pk, up to VIEW_VAULTS_MAX_SCAN vaults
+pub fn view_vault_totals(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let prefix = bcat(&[b"bic:lockup_prime:vault:", &args[0], b":"]);
+
+    let mut total_amount: i128 = 0;
+    let mut total_prime_points: i128 = 0;
+    let mut cursor = Vec::new();
+    let mut scanned = 0;
+    while let Some((index, val)) = kv_get_next(env, &prefix, &cursor) {
+        scanned += 1;
+        if scanned > protocol::VIEW_VAULTS_MAX_SCAN { panic_any("too_many_vaults") }
+        let (_tier, multiplier, _unlock_epoch, amount) = decode_vault(&val);
+        total_amount += amount as i128;
+        total_prime_points += (amount as i128) * (multiplier as i128);
+        cursor = index;
+    }
+
+    encode(Term::PropList(vec![
+        (Term::Binary(b"total_amount".to_vec()), Term::VarInt(total_amount)),
+        (Term::Binary(b"total_prime_points".to_vec()), Term::VarInt(total_prime_points)),
+    ]))
+}
+
 pub fn call_unlock(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
     if args.len() != 1 { panic_any("invalid_args") }
     let vault_index = args[0].as_slice();
@@ -66,14 +129,7 @@ pub fn call_unlock(env: &mut crate::consensus::consensus_apply::ApplyEnv, args:
     if vault.is_none() { panic_any("invalid_vault") }
     let vault = vault.unwrap();
 
-    let vault_parts: Vec<Vec<u8>> = vault.split(|&b| b == b'-').map(|seg| seg.to_vec()).collect();
-    let tier = vault_parts[0].as_slice();
-    let multiplier = vault_parts[1].as_slice();
-    let multiplier = std::str::from_utf8(&multiplier).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_multiplier"));
-    let unlock_epoch = vault_parts[2].as_slice();
-    let unlock_epoch = std::str::from_utf8(&unlock_epoch).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_unlock_epoch"));
-    let unlock_amount = vault_parts[3].as_slice();
-    let unlock_amount = std::str::from_utf8(&unlock_amount).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_unlock_amount"));
+    let (_tier, multiplier, unlock_epoch, unlock_amount) = decode_vault(&vault);
 
     if env.caller_env.entry_epoch < unlock_epoch {
         let penalty = unlock_amount / 4;
@@ -1,3 +1,4 @@
+pub mod abi;
 pub mod coin;
 pub mod coin_symbol_reserved;
 pub mod contract;
@@ -6,12 +7,22 @@ pub mod lockup;
 pub mod lockup_prime;
 pub mod nft;
 pub mod protocol;
+pub mod schedule;
 pub mod sol;
 pub mod sol_bloom;
 pub mod sol_difficulty;
 pub mod sol_freivalds;
 pub mod exsss;
 pub mod wasm;
+pub mod wasm_profile;
+
+//events raised by builtin contracts, charged and capped like contract events
+pub fn emit_event(env: &mut crate::consensus::consensus_apply::ApplyEnv, contract: &[u8], topics: Vec<Vec<u8>>, data: Vec<u8>) {
+    if env.events.len() >= protocol::EVENT_TOTAL_ELEMENTS { std::panic::panic_any("exec_events_total_elements_exceeded") }
+    let size = topics.iter().map(|t| t.len()).sum::<usize>() + data.len();
+    crate::consensus::consensus_kv::storage_budget_decr(env, protocol::COST_PER_BYTE_HISTORICAL * size as i128);
+    env.events.push(crate::model::tx_receipt::TXEvent { contract: contract.to_vec(), topics, data });
+}
 
 pub fn list_of_binaries_to_vecpak(list_of_binaries: Vec<Vec<u8>>) -> Vec<u8> {
     let elements: Vec<vecpak::Term> = list_of_binaries
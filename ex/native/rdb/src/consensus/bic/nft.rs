@@ -1,7 +1,7 @@
 use std::panic::panic_any;
 use crate::{bcat, consensus};
-use crate::consensus::{bic::{coin::BURN_ADDRESS}};
-use crate::consensus::consensus_kv::{kv_get, kv_put, kv_increment, kv_exists};
+use crate::consensus::{bic::{coin::BURN_ADDRESS, protocol}};
+use crate::consensus::consensus_kv::{kv_get, kv_put, kv_increment, kv_exists, kv_delete};
 use vecpak::{encode, decode, Term};
 
 pub fn balance_burnt(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8], token: &[u8]) -> i128 {
@@ -30,6 +30,47 @@ pub fn soulbound(env: &mut crate::consensus::consensus_apply::ApplyEnv, collecti
     }
 }
 
+fn token_key(collection: &[u8], token: &[u8], field: &[u8]) -> Vec<u8> {
+    bcat(&[b"nft:", collection, b":token:", token, b":", field])
+}
+
+fn parse_i128(env: &mut crate::consensus::consensus_apply::ApplyEnv, key: &[u8]) -> Option<i128> {
+    kv_get(env, key).map(|v| std::str::from_utf8(&v).unwrap().parse::<i128>().unwrap_or_else(|_| panic_any("invalid_number")))
+}
+
+//collections created before minted was tracked have mints the counter never saw
+pub fn supply_tracked(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8]) -> bool {
+    kv_exists(env, &bcat(&[b"nft:", collection, b":supply_tracked"]))
+}
+
+//total ever minted for a token, only complete when the collection is supply_tracked
+pub fn minted(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8], token: &[u8]) -> i128 {
+    parse_i128(env, &token_key(collection, token, b"minted")).unwrap_or(0)
+}
+
+pub fn max_supply(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8], token: &[u8]) -> Option<i128> {
+    parse_i128(env, &token_key(collection, token, b"max_supply"))
+}
+
+//the per token override wins, otherwise base_uri followed by the token id
+pub fn token_uri(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8], token: &[u8]) -> Vec<u8> {
+    match kv_get(env, &token_key(collection, token, b"uri")) {
+        Some(uri) => uri,
+        None => match kv_get(env, &bcat(&[b"nft:", collection, b":base_uri"])) {
+            Some(base_uri) => bcat(&[&base_uri, token]),
+            None => Vec::new()
+        }
+    }
+}
+
+fn check_text(value: &[u8], max: usize, error: &'static str) {
+    if value.len() > max || std::str::from_utf8(value).is_err() { panic_any(error) }
+}
+
+fn put_or_delete(env: &mut crate::consensus::consensus_apply::ApplyEnv, key: &[u8], value: &[u8]) {
+    if value.is_empty() { kv_delete(env, key) } else { kv_put(env, key, value) }
+}
+
 pub fn has_permission(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8], signer: &[u8]) -> bool {
     match view_account(env, collection) {
         None => false,
@@ -47,17 +88,99 @@ pub fn call_transfer(env: &mut crate::consensus::consensus_apply::ApplyEnv, args
     let collection = args[2].as_slice();
     let token = args[3].as_slice();
 
+    let sender = env.caller_env.account_caller.clone();
+    transfer(env, &sender, receiver, amount, collection, token);
+}
+
+pub fn transfer(env: &mut crate::consensus::consensus_apply::ApplyEnv, sender: &[u8], receiver: &[u8], amount: i128, collection: &[u8], token: &[u8]) {
     if receiver.len() != 48 { panic_any("invalid_receiver_pk") }
     if !(consensus::bls12_381::validate_public_key(receiver) || receiver == &BURN_ADDRESS) { panic_any("invalid_receiver_pk") }
     if amount <= 0 { panic_any("invalid_amount") }
-    if amount > balance(env, &env.caller_env.account_caller.clone(), &collection, &token) { panic_any("insufficient_tokens") }
+    if amount > balance(env, sender, &collection, &token) { panic_any("insufficient_tokens") }
 
     if soulbound(env, collection) { panic_any("soulbound") }
 
-    kv_increment(env, &bcat(&[b"account:", &env.caller_env.account_caller, b":nft:", collection, b":", token]), -amount);
+    kv_increment(env, &bcat(&[b"account:", sender, b":nft:", collection, b":", token]), -amount);
     kv_increment(env, &bcat(&[b"account:", receiver, b":nft:", collection, b":", token]), amount);
 }
 
+//per token approvals hold an amount, operators can move any token of the collection
+fn approval_key(owner: &[u8], spender: &[u8], collection: &[u8], token: &[u8]) -> Vec<u8> {
+    bcat(&[b"account:", owner, b":nft_approval:", spender, b":", collection, b":", token])
+}
+
+fn operator_key(owner: &[u8], operator: &[u8], collection: &[u8]) -> Vec<u8> {
+    bcat(&[b"account:", owner, b":nft_operator:", operator, b":", collection])
+}
+
+pub fn approved(env: &mut crate::consensus::consensus_apply::ApplyEnv, owner: &[u8], spender: &[u8], collection: &[u8], token: &[u8]) -> i128 {
+    parse_i128(env, &approval_key(owner, spender, collection, token)).unwrap_or(0)
+}
+
+pub fn is_operator(env: &mut crate::consensus::consensus_apply::ApplyEnv, owner: &[u8], operator: &[u8], collection: &[u8]) -> bool {
+    kv_get(env, &operator_key(owner, operator, collection)).as_deref() == Some(b"true")
+}
+
+fn check_spender(env: &mut crate::consensus::consensus_apply::ApplyEnv, spender: &[u8], collection: &[u8]) {
+    if spender.len() != 48 || !consensus::bls12_381::validate_public_key(spender) { panic_any("invalid_spender_pk") }
+    if spender == env.caller_env.account_caller.as_slice() { panic_any("spender_is_owner") }
+    if !exists(env, collection) { panic_any("collection_doesnt_exist") }
+    if soulbound(env, collection) { panic_any("soulbound") }
+}
+
+//spender, amount, collection, token, an amount of 0 clears the approval
+pub fn call_approve(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 4 { panic_any("invalid_args") }
+    let spender = args[0].as_slice();
+    let amount = std::str::from_utf8(&args[1]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    let collection = args[2].as_slice();
+    let token = args[3].as_slice();
+    check_spender(env, spender, collection);
+    if amount < 0 { panic_any("invalid_amount") }
+
+    let owner = env.caller_env.account_caller.clone();
+    let key = approval_key(&owner, spender, collection, token);
+    if amount == 0 { kv_delete(env, &key) } else { kv_put(env, &key, amount.to_string().as_bytes()) }
+}
+
+//operator, collection, true|false
+pub fn call_set_approval_for_all(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let operator = args[0].as_slice();
+    let collection = args[1].as_slice();
+    check_spender(env, operator, collection);
+
+    let owner = env.caller_env.account_caller.clone();
+    let key = operator_key(&owner, operator, collection);
+    match args[2].as_slice() {
+        b"true" => kv_put(env, &key, b"true"),
+        b"false" => kv_delete(env, &key),
+        _ => panic_any("invalid_direction")
+    }
+}
+
+//owner, receiver, amount, collection, token moved by an operator or approved spender
+pub fn call_transfer_from(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 5 { panic_any("invalid_args") }
+    let owner = args[0].as_slice();
+    let receiver = args[1].as_slice();
+    let amount = std::str::from_utf8(&args[2]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    let collection = args[3].as_slice();
+    let token = args[4].as_slice();
+    let spender = env.caller_env.account_caller.clone();
+
+    if owner.len() != 48 { panic_any("invalid_owner_pk") }
+    if amount <= 0 { panic_any("invalid_amount") }
+    if !is_operator(env, owner, &spender, collection) {
+        let current = approved(env, owner, &spender, collection, token);
+        if amount > current { panic_any("not_approved") }
+        let key = approval_key(owner, &spender, collection, token);
+        if current == amount { kv_delete(env, &key) } else { kv_put(env, &key, (current - amount).to_string().as_bytes()) }
+    }
+
+    transfer(env, owner, receiver, amount, collection, token);
+}
+
 pub fn call_create_collection(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
     if args.len() < 2 { panic_any("invalid_args") }
     let collection_original = args[0].as_slice();
@@ -72,8 +195,152 @@ pub fn call_create_collection(env: &mut crate::consensus::consensus_apply::Apply
     if exists(env, &collection) { panic_any("collection_exists") }
 
     kv_put(env, &bcat(&[b"nft:", &collection, b":view_account"]), &env.caller_env.account_caller.clone());
+    kv_put(env, &bcat(&[b"nft:", &collection, b":supply_tracked"]), b"true");
 
     if soulbound == b"true" { kv_put(env, &bcat(&[b"nft:", &collection, b":soulbound"]), b"true") }
+
+    let name = args.get(2).map(|v| v.as_slice()).unwrap_or(b"");
+    let base_uri = args.get(3).map(|v| v.as_slice()).unwrap_or(b"");
+    put_collection_metadata(env, &collection, name, base_uri);
+
+    let royalty_recipient = args.get(4).map(|v| v.as_slice()).unwrap_or(b"");
+    let royalty_bps = args.get(5).map(|v| v.as_slice()).unwrap_or(b"");
+    put_royalty(env, &collection, royalty_recipient, royalty_bps);
+}
+
+//an empty recipient clears the royalty
+fn put_royalty(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8], recipient: &[u8], bps: &[u8]) {
+    if recipient.is_empty() {
+        kv_delete(env, &bcat(&[b"nft:", collection, b":royalty_recipient"]));
+        kv_delete(env, &bcat(&[b"nft:", collection, b":royalty_bps"]));
+        return
+    }
+    if recipient.len() != 48 || !consensus::bls12_381::validate_public_key(recipient) { panic_any("invalid_royalty_recipient") }
+    let bps = std::str::from_utf8(bps).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_royalty_bps"));
+    if bps <= 0 || bps > protocol::NFT_MAX_ROYALTY_BPS { panic_any("invalid_royalty_bps") }
+
+    kv_put(env, &bcat(&[b"nft:", collection, b":royalty_recipient"]), recipient);
+    kv_put(env, &bcat(&[b"nft:", collection, b":royalty_bps"]), bps.to_string().as_bytes());
+}
+
+pub fn royalty(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8]) -> Option<(Vec<u8>, i128)> {
+    let recipient = kv_get(env, &bcat(&[b"nft:", collection, b":royalty_recipient"]))?;
+    let bps = parse_i128(env, &bcat(&[b"nft:", collection, b":royalty_bps"]))?;
+    Some((recipient, bps))
+}
+
+//rounds down so the seller never pays more than bps of the price
+pub fn royalty_amount(price: i128, bps: i128) -> i128 {
+    if price < 0 { panic_any("invalid_amount") }
+    price.checked_mul(bps).unwrap_or_else(|| panic_any("invalid_amount")) / protocol::NFT_MAX_ROYALTY_BPS
+}
+
+//collection, recipient, bps
+pub fn call_set_royalty(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let collection = args[0].as_slice();
+    if !exists(env, collection) { panic_any("collection_doesnt_exist") }
+    if !has_permission(env, collection, &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
+
+    put_royalty(env, collection, &args[1], &args[2]);
+}
+
+//collection, sale_price -> %{recipient, bps, amount}, amount rounds down and is 0 without a royalty
+pub fn view_royalty(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    if args.len() != 2 { panic_any("invalid_args") }
+    let collection = args[0].as_slice();
+    let price = std::str::from_utf8(&args[1]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    if !exists(env, collection) { panic_any("collection_doesnt_exist") }
+
+    let (recipient, bps) = royalty(env, collection).unwrap_or_default();
+    let amount = royalty_amount(price, bps);
+    encode(Term::PropList(vec![
+        (Term::Binary(b"recipient".to_vec()), Term::Binary(recipient)),
+        (Term::Binary(b"bps".to_vec()), Term::VarInt(bps)),
+        (Term::Binary(b"amount".to_vec()), Term::VarInt(amount)),
+    ]))
+}
+
+fn put_collection_metadata(env: &mut crate::consensus::consensus_apply::ApplyEnv, collection: &[u8], name: &[u8], base_uri: &[u8]) {
+    check_text(name, 64, "invalid_name");
+    check_text(base_uri, 256, "invalid_uri");
+    put_or_delete(env, &bcat(&[b"nft:", collection, b":name"]), name);
+    put_or_delete(env, &bcat(&[b"nft:", collection, b":base_uri"]), base_uri);
+}
+
+pub fn call_set_collection_metadata(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let collection = args[0].as_slice();
+    if !exists(env, collection) { panic_any("collection_doesnt_exist") }
+    if !has_permission(env, collection, &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
+
+    put_collection_metadata(env, collection, &args[1], &args[2]);
+}
+
+//collection, token, uri, max_supply, an empty max_supply leaves the cap unchanged
+//a cap can be lowered down to the minted amount but never raised
+//legacy collections cannot be capped as their real supply is unknown
+pub fn call_set_token_metadata(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 4 { panic_any("invalid_args") }
+    let collection = args[0].as_slice();
+    let token = args[1].as_slice();
+    let uri = args[2].as_slice();
+    if !exists(env, collection) { panic_any("collection_doesnt_exist") }
+    if !has_permission(env, collection, &env.caller_env.account_caller.clone()) { panic_any("no_permissions") }
+    if token.len() < 1 { panic_any("token_too_short") }
+    if token.len() > 32 { panic_any("token_too_long") }
+
+    check_text(uri, 256, "invalid_uri");
+    put_or_delete(env, &token_key(collection, token, b"uri"), uri);
+
+    if !args[3].is_empty() {
+        let cap = std::str::from_utf8(&args[3]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_max_supply"));
+        if cap <= 0 { panic_any("invalid_max_supply") }
+        if !supply_tracked(env, collection) { panic_any("supply_untracked") }
+        if cap < minted(env, collection, token) { panic_any("max_supply_below_minted") }
+        if max_supply(env, collection, token).map_or(false, |current| cap > current) { panic_any("max_supply_cannot_increase") }
+        kv_put(env, &token_key(collection, token, b"max_supply"), cap.to_string().as_bytes());
+    }
+}
+
+//%{collection, name, base_uri, owner, soulbound, royalty_recipient, royalty_bps}
+pub fn view_collection(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    if args.len() != 1 { panic_any("invalid_args") }
+    let collection = args[0].as_slice();
+    let owner = view_account(env, collection).unwrap_or_else(|| panic_any("collection_doesnt_exist"));
+    let (royalty_recipient, royalty_bps) = royalty(env, collection).unwrap_or_default();
+
+    encode(Term::PropList(vec![
+        (Term::Binary(b"collection".to_vec()), Term::Binary(collection.to_vec())),
+        (Term::Binary(b"name".to_vec()), Term::Binary(kv_get(env, &bcat(&[b"nft:", collection, b":name"])).unwrap_or_default())),
+        (Term::Binary(b"base_uri".to_vec()), Term::Binary(kv_get(env, &bcat(&[b"nft:", collection, b":base_uri"])).unwrap_or_default())),
+        (Term::Binary(b"owner".to_vec()), Term::Binary(owner)),
+        (Term::Binary(b"soulbound".to_vec()), Term::Bool(soulbound(env, collection))),
+        (Term::Binary(b"royalty_recipient".to_vec()), Term::Binary(royalty_recipient)),
+        (Term::Binary(b"royalty_bps".to_vec()), Term::VarInt(royalty_bps)),
+    ]))
+}
+
+//%{collection, token, uri, burned, minted, max_supply}, minted only for supply_tracked collections
+pub fn view_token(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: &[Vec<u8>]) -> Vec<u8> {
+    if args.len() != 2 { panic_any("invalid_args") }
+    let collection = args[0].as_slice();
+    let token = args[1].as_slice();
+    if !exists(env, collection) { panic_any("collection_doesnt_exist") }
+
+    let mut pairs = vec![
+        (Term::Binary(b"collection".to_vec()), Term::Binary(collection.to_vec())),
+        (Term::Binary(b"token".to_vec()), Term::Binary(token.to_vec())),
+        (Term::Binary(b"uri".to_vec()), Term::Binary(token_uri(env, collection, token))),
+        (Term::Binary(b"burned".to_vec()), Term::VarInt(balance_burnt(env, collection, token))),
+    ];
+    if supply_tracked(env, collection) {
+        pairs.push((Term::Binary(b"minted".to_vec()), Term::VarInt(minted(env, collection, token))));
+    }
+    if let Some(cap) = max_supply(env, collection, token) {
+        pairs.push((Term::Binary(b"max_supply".to_vec()), Term::VarInt(cap)));
+    }
+    encode(Term::PropList(pairs))
 }
 
 pub fn call_mint(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
@@ -106,5 +373,130 @@ pub fn mint(env: &mut crate::consensus::consensus_apply::ApplyEnv, receiver: &[u
 
     if !exists(env, &collection) { panic_any("collection_doesnt_exist") }
 
+    let minted = kv_increment(env, &token_key(collection, token, b"minted"), amount);
+    if max_supply(env, collection, token).map_or(false, |cap| minted > cap) { panic_any("exceeds_max_supply") }
+
     kv_increment(env, &bcat(&[b"account:", receiver, b":nft:", collection, b":", token]), amount);
 }
+
+//burning moves the tokens to BURN_ADDRESS so balance_burnt is the single burned count
+//burnt tokens still count as minted so a capped token cannot be reminted
+pub fn call_burn(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let amount = std::str::from_utf8(&args[0]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    let owner = env.caller_env.account_caller.clone();
+    burn(env, &owner, amount, &args[1], &args[2]);
+}
+
+pub fn burn(env: &mut crate::consensus::consensus_apply::ApplyEnv, owner: &[u8], amount: i128, collection: &[u8], token: &[u8]) {
+    if amount <= 0 { panic_any("invalid_amount") }
+    if !exists(env, collection) { panic_any("collection_doesnt_exist") }
+    if amount > balance(env, owner, collection, token) { panic_any("insufficient_tokens") }
+
+    kv_increment(env, &bcat(&[b"account:", owner, b":nft:", collection, b":", token]), -amount);
+    kv_increment(env, &bcat(&[b"account:", &BURN_ADDRESS, b":nft:", collection, b":", token]), amount);
+}
+
+//vecpak list of [token, amount] pairs, amounts as ascii or varint
+fn batch_items(env: &mut crate::consensus::consensus_apply::ApplyEnv, items: &[u8]) -> Vec<(Vec<u8>, i128)> {
+    let Ok(Term::List(list)) = decode(items) else { panic_any("invalid_batch") };
+    if list.is_empty() { panic_any("empty_batch") }
+    if list.len() > protocol::NFT_BATCH_MAX_ITEMS { panic_any("batch_too_large") }
+    consensus::consensus_kv::exec_budget_decr(env, protocol::COST_PER_BATCH_ITEM * list.len() as i128);
+
+    list.into_iter().map(|item| {
+        let Term::List(pair) = item else { panic_any("invalid_batch_item") };
+        match pair.as_slice() {
+            [Term::Binary(token), Term::VarInt(amount)] => (token.clone(), *amount),
+            [Term::Binary(token), Term::Binary(amount)] => {
+                let amount = std::str::from_utf8(amount).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+                (token.clone(), amount)
+            }
+            _ => panic_any("invalid_batch_item")
+        }
+    }).collect()
+}
+
+//receiver, collection, items
+pub fn call_mint_batch(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let receiver = args[0].as_slice();
+    let collection = args[1].as_slice();
+    if receiver.len() != 48 { panic_any("invalid_receiver_pk") }
+    if !has_permission(env, collection, &env.caller_env.account_caller.clone()) {
+        if !exists(env, collection) { panic_any("collection_doesnt_exist") }
+        panic_any("no_permissions")
+    }
+
+    for (token, amount) in batch_items(env, &args[2]) {
+        if token.len() < 1 { panic_any("token_too_short") }
+        if token.len() > 32 { panic_any("token_too_long") }
+        mint(env, receiver, amount, collection, &token);
+    }
+}
+
+//receiver, collection, items
+pub fn call_transfer_batch(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 3 { panic_any("invalid_args") }
+    let receiver = args[0].as_slice();
+    let collection = args[1].as_slice();
+    let sender = env.caller_env.account_caller.clone();
+
+    for (token, amount) in batch_items(env, &args[2]) {
+        transfer(env, &sender, receiver, amount, collection, &token);
+    }
+}
+
+//collection, items
+pub fn call_burn_batch(env: &mut crate::consensus::consensus_apply::ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 2 { panic_any("invalid_args") }
+    let collection = args[0].as_slice();
+    let owner = env.caller_env.account_caller.clone();
+
+    for (token, amount) in batch_items(env, &args[1]) {
+        burn(env, &owner, amount, collection, &token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::consensus_apply::with_test_env;
+    use crate::consensus::consensus_apply::test_util::{args, as_caller, err, pk};
+
+    #[test]
+    fn royalty_amount_rounds_down() {
+        assert_eq!(royalty_amount(10_000, 250), 250);
+        assert_eq!(royalty_amount(999, 250), 24);
+        assert_eq!(royalty_amount(39, 250), 0);
+        assert_eq!(royalty_amount(1, protocol::NFT_MAX_ROYALTY_BPS), 1);
+        assert_eq!(royalty_amount(0, 250), 0);
+        assert_eq!(royalty_amount(1_000, 0), 0);
+    }
+
+    #[test]
+    fn royalty_amount_rejects_bad_prices() {
+        assert_eq!(err(|| { royalty_amount(-1, 250); }), "invalid_amount");
+        assert_eq!(err(|| { royalty_amount(i128::MAX, 2); }), "invalid_amount");
+    }
+
+    #[test]
+    fn royalty_set_view_and_clear() {
+        with_test_env(true, |env| {
+            let (owner, recipient) = (pk(1), pk(2));
+            as_caller(env, &owner);
+            call_create_collection(env, args(&[b"ART", b""]));
+
+            call_set_royalty(env, args(&[b"ART", &recipient, b"333"]));
+            assert_eq!(royalty(env, b"ART"), Some((recipient.clone(), 333)));
+            let view = decode(&view_royalty(env, &args(&[b"ART", b"1001"]))).unwrap();
+            let Term::PropList(pairs) = view else { panic!("not a proplist") };
+            assert!(matches!(crate::model::_codec::pl_find_opt(&pairs, b"amount"), Some(Term::VarInt(33))));
+            assert_eq!(crate::model::_codec::pl_get_bytes_opt(&pairs, b"recipient"), Some(recipient.as_slice()));
+
+            assert_eq!(err(|| call_set_royalty(env, args(&[b"ART", &recipient, b"10001"]))), "invalid_royalty_bps");
+            call_set_royalty(env, args(&[b"ART", b"", b""]));
+            assert_eq!(royalty(env, b"ART"), None);
+        });
+    }
+}
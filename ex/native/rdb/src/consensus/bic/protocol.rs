@@ -19,6 +19,11 @@ pub const AMA_01_CENT: i128 =      1_000_000;
 
 pub const RESERVE_AMA_PER_TX_EXEC: i128 = AMA_10_CENT; //reserved for exec balance (refunded at end of TX execution)
 pub const RESERVE_AMA_PER_TX_STORAGE: i128 = AMA_1_DOLLAR; //reserved for storage writes
+pub const TX_MAX_ACTIONS: usize = 8; //actions executed atomically in a single tx
+//a tx may set its own exec_limit and storage_limit within these bounds, defaults are the reserves above
+pub const TX_MIN_EXEC_LIMIT: i128 = AMA_01_CENT;
+pub const TX_MAX_EXEC_LIMIT: i128 = AMA_1_DOLLAR;
+pub const TX_MAX_STORAGE_LIMIT: i128 = 10 * AMA_1_DOLLAR;
 
 pub const COST_PER_BYTE_HISTORICAL: i128 = 6_666; //cost to increase the ledger size
 pub const COST_PER_BYTE_STATE: i128 = 16_666; //cost to grow the contract state
@@ -27,6 +32,8 @@ pub const COST_PER_OP_WASM: i128 = 1; //cost to execute a wasm op
 pub const COST_PER_DB_READ_BASE: i128 = 5_000 * 10;
 pub const COST_PER_DB_READ_BYTE: i128 = 50;
 
+pub const COST_PER_DB_SCAN_ITEM: i128 = 500 * 10; //per item returned by a range scan, on top of the read base
+
 pub const COST_PER_DB_WRITE_BASE: i128 = 25_000 * 10;
 pub const COST_PER_DB_WRITE_BYTE: i128 = 250;
 
@@ -52,16 +59,35 @@ pub const COST_PER_CALL: i128 = AMA_01_CENT;
 pub const COST_PER_DEPLOY: i128 = AMA_1_CENT; //cost to deploy contract
 pub const COST_PER_SOL: i128 = AMA_1_CENT; //cost to submit_sol
 pub const COST_PER_NEW_LEAF_MERKLE: i128 = COST_PER_BYTE_STATE * 128; //cost to grow the merkle tree
+pub const COST_PER_BATCH_ITEM: i128 = COST_PER_DB_WRITE_BASE; //per (token, amount) pair in Nft batch calls
+pub const NFT_BATCH_MAX_ITEMS: usize = 64; //pairs in a single Nft batch call
+pub const NFT_MAX_ROYALTY_BPS: i128 = 10_000; //basis points, 10_000 is the full sale price
+
+pub const SCHEDULE_MAX_DELAY: u64 = 1_000_000; //furthest height ahead a callback can be registered
+pub const SCHEDULE_MAX_PER_HEIGHT: i128 = 16; //callbacks executed at the end of a single entry
+pub const SCHEDULE_MIN_BUDGET: i128 = AMA_01_CENT;
+pub const SCHEDULE_MAX_BUDGET: i128 = AMA_10_CENT; //prepaid exec and storage for one callback
 
 pub const LOG_MSG_SIZE: usize = 4096; //max log line length
 pub const LOG_TOTAL_SIZE: usize = 16384; //max log total size
 pub const LOG_TOTAL_ELEMENTS: usize = 32; //max elements in list
+pub const EVENT_MAX_TOPICS: usize = 4; //max indexed topics per event
+pub const EVENT_TOPIC_SIZE: usize = 64; //max topic length
+pub const EVENT_DATA_SIZE: usize = 4096; //max event data length
+pub const EVENT_TOTAL_ELEMENTS: usize = 32; //max events per tx
 pub const WASM_MAX_PTR_LEN: usize = 1048576; //largest term passable from inside WASM to HOST
 //pub const WASM_MAX_PTR_LEN: usize = 32768; //dont smash passed first page
 pub const WASM_MAX_PANIC_MSG_SIZE: usize = 128;
 
 pub const MAX_DB_KEY_SIZE: usize = 512;
 pub const MAX_DB_VALUE_SIZE: usize = 1048576;
+//contract values are read back through the io region together with their key, a lone scan result adds its count
+pub const WASM_MAX_KV_VALUE_SIZE: usize = (WASM_LAYOUT_RESERVED_END - WASM_LAYOUT_IO) as usize - 12 - MAX_DB_KEY_SIZE;
+
+pub const VIEW_VAULTS_MAX_ITEMS: usize = 64; //vaults returned by a single view_vaults page
+pub const VIEW_VAULTS_MAX_SCAN: usize = 4096; //vaults summed by a single view_vault_totals
+pub const KV_SCAN_MAX_ITEMS: usize = 256; //max pairs returned by a single scan
+pub const KV_SCAN_MAX_BYTES: usize = 32768; //max packed scan result unless the first pair alone is larger
 
 pub const WASM_MAX_BINARY_SIZE: usize = 1048576;
 pub const WASM_MAX_FUNCTIONS: u32 = 1000;
@@ -69,13 +95,65 @@ pub const WASM_MAX_GLOBALS: u32 = 100;
 pub const WASM_MAX_EXPORTS: u32 = 50;
 pub const WASM_MAX_IMPORTS: u32 = 50;
 
+//wasm proposals contracts may use, any other instruction is rejected at deploy
+pub const WASM_ALLOWED_PROPOSALS: &[&str] = &["mvp", "sign_extension", "saturating_float_to_int", "bulk_memory"];
+//canonicalize_nans makes float results deterministic only for the singlepass backend it is set on,
+//a compiler or target change would fork consensus silently, so every float instruction is refused
+//and canonicalization stays as a backstop for what already got deployed
+pub const WASM_ALLOW_FLOATS: bool = false;
+pub const WASM_MAX_AUDIT_FINDINGS: usize = 16; //offending instructions reported back through logs
+
+//linear memory in 64KiB pages, the module may import more than the initial but never past the max
+pub const WASM_PAGE_SIZE: u64 = 65536;
+pub const WASM_MEMORY_INITIAL_PAGES: u64 = 2;
+pub const WASM_MEMORY_MAX_PAGES: u64 = 30;
+
+//memory layout shared with the contract SDKs, bump the version on any change
+//[0, WASM_LAYOUT_RESERVED_END) belongs to the host, data segments and the stack must live above it
+pub const WASM_LAYOUT_VERSION: u32 = 1;
+pub const WASM_LAYOUT_RESERVED_END: u64 = 65536;
+pub const WASM_LAYOUT_VERSION_PTR: u64 = 1_024; //u32
+pub const WASM_LAYOUT_SEED: u64 = 1_100; //[len u32][bytes] for the rest
+pub const WASM_LAYOUT_ENTRY_SLOT: u64 = 2_000; //u64
+pub const WASM_LAYOUT_ENTRY_HEIGHT: u64 = 2_010; //u64
+pub const WASM_LAYOUT_ENTRY_EPOCH: u64 = 2_020; //u64
+pub const WASM_LAYOUT_ENTRY_SIGNER: u64 = 2_100;
+pub const WASM_LAYOUT_ENTRY_PREV_HASH: u64 = 2_200;
+pub const WASM_LAYOUT_ENTRY_VR: u64 = 2_300;
+pub const WASM_LAYOUT_ENTRY_DR: u64 = 2_400;
+pub const WASM_LAYOUT_TX_NONCE: u64 = 3_000; //u64
+pub const WASM_LAYOUT_TX_SIGNER: u64 = 3_100;
+pub const WASM_LAYOUT_ACCOUNT_CURRENT: u64 = 4_000;
+pub const WASM_LAYOUT_ACCOUNT_CALLER: u64 = 4_100;
+pub const WASM_LAYOUT_ACCOUNT_ORIGIN: u64 = 4_200;
+pub const WASM_LAYOUT_ATTACHED_SYMBOL: u64 = 5_000;
+pub const WASM_LAYOUT_ATTACHED_AMOUNT: u64 = 5_100;
+pub const WASM_LAYOUT_IO: u64 = 10_000; //function args on entry, host call results after
+
 pub fn pay_cost(env: &mut crate::consensus::consensus_apply::ApplyEnv, cost: i128) {
-    consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &env.caller_env.account_origin, b":balance:AMA"]), -cost);
+    consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &env.caller_env.account_payer, b":balance:AMA"]), -cost);
     // Increment validator / burn
     consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &env.caller_env.entry_signer, b":balance:AMA"]), cost/2);
     consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &coin::BURN_ADDRESS, b":balance:AMA"]), cost/2);
 }
 
+pub fn tx_limits_valid(tx: &crate::model::tx::TX) -> bool {
+    (TX_MIN_EXEC_LIMIT..=TX_MAX_EXEC_LIMIT).contains(&tx.exec_limit())
+        && (0..=TX_MAX_STORAGE_LIMIT).contains(&tx.storage_limit())
+}
+
+//txs without limits keep the legacy path, the default reserve is always debited and the tx runs
+pub fn tx_has_custom_limits(tx: &crate::model::tx::TX, testnet: bool) -> bool {
+    testnet && (tx.exec_limit.is_some() || tx.storage_limit.is_some())
+}
+
+//exec and storage deposits of a tx with custom limits, None when the payer cannot cover them
+pub fn tx_reservation(tx: &crate::model::tx::TX, payer_balance: i128) -> Option<(i128, i128)> {
+    let (exec_limit, storage_limit) = (tx.exec_limit(), tx.storage_limit());
+    if !tx_limits_valid(tx) || payer_balance < exec_limit + storage_limit { return None }
+    Some((exec_limit, storage_limit))
+}
+
 pub fn tx_historical_cost(txu: &crate::model::tx::TXU) -> i128 {
     std::cmp::max(
             AMA_1_CENT,
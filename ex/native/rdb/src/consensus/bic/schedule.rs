@@ -0,0 +1,245 @@
+use std::panic::panic_any;
+use crate::consensus::consensus_apply::ApplyEnv;
+use crate::{bcat};
+use crate::consensus::bic::{coin::balance, protocol};
+use crate::consensus::{consensus_kv::{kv_get, kv_get_next, kv_increment, kv_put, kv_delete}};
+use crate::model::_codec::{pl_find_opt};
+use vecpak::{encode, decode, Term};
+
+//queue is ordered by padded height, one slot per contract and function at a height
+//bic:schedule:queue:<height>:<contract>:<function> -> %{budget, args}
+pub struct Scheduled {
+    pub id: [u8; 32],
+    pub contract: [u8; 48],
+    pub function: Vec<u8>,
+    pub args: Vec<Vec<u8>>,
+    pub budget: i128,
+}
+
+fn queue_prefix(height: u64) -> Vec<u8> {
+    bcat(&[b"bic:schedule:queue:", format!("{:012}", height).as_bytes(), b":"])
+}
+
+fn count_key(height: u64) -> Vec<u8> {
+    bcat(&[b"bic:schedule:count:", format!("{:012}", height).as_bytes()])
+}
+
+fn queue_key(height: u64, contract: &[u8], function: &[u8]) -> Vec<u8> {
+    bcat(&[&queue_prefix(height), contract, b":", function])
+}
+
+fn parse_height(height: &[u8]) -> u64 {
+    std::str::from_utf8(height).ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or_else(|| panic_any("invalid_height"))
+}
+
+fn budget_of(value: &[u8]) -> i128 {
+    let term = decode(value).unwrap_or_else(|_| panic_any("invalid_schedule_entry"));
+    let Term::PropList(pairs) = term else { panic_any("invalid_schedule_entry") };
+    match pl_find_opt(&pairs, b"budget") {
+        Some(Term::VarInt(budget)) => *budget,
+        _ => panic_any("invalid_schedule_entry")
+    }
+}
+
+//called by a contract, the budget is taken from the contract AMA balance up front
+pub fn call_register(env: &mut ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() < 3 { panic_any("invalid_args") }
+    let height = parse_height(args[0].as_slice());
+    let function = args[1].as_slice();
+    let budget = std::str::from_utf8(&args[2]).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_budget"));
+    let call_args = args[3..].to_vec();
+    let contract = env.caller_env.account_caller.clone();
+
+    if crate::consensus::bic::contract::bytecode(env, &contract).is_none() { panic_any("caller_not_contract") }
+    if height <= env.caller_env.entry_height { panic_any("schedule_height_not_in_future") }
+    if height > env.caller_env.entry_height.saturating_add(protocol::SCHEDULE_MAX_DELAY) { panic_any("schedule_height_too_far") }
+    if function.is_empty() || function.len() > 64 { panic_any("invalid_function") }
+    if budget < protocol::SCHEDULE_MIN_BUDGET || budget > protocol::SCHEDULE_MAX_BUDGET { panic_any("invalid_budget") }
+    if call_args.len() > 16 { panic_any("too_many_args") }
+
+    let key = queue_key(height, &contract, function);
+    if kv_get(env, &key).is_some() { panic_any("schedule_already_registered") }
+    if kv_increment(env, &count_key(height), 1) > protocol::SCHEDULE_MAX_PER_HEIGHT { panic_any("schedule_height_full") }
+
+    if balance(env, &contract, b"AMA") < budget { panic_any("insufficient_funds") }
+    kv_increment(env, &bcat(&[b"account:", &contract, b":balance:AMA"]), -budget);
+
+    kv_put(env, &key, &encode(Term::PropList(vec![
+        (Term::Binary(b"budget".to_vec()), Term::VarInt(budget)),
+        (Term::Binary(b"args".to_vec()), Term::List(call_args.into_iter().map(Term::Binary).collect())),
+    ])));
+}
+
+pub fn call_cancel(env: &mut ApplyEnv, args: Vec<Vec<u8>>) {
+    if args.len() != 2 { panic_any("invalid_args") }
+    let height = parse_height(args[0].as_slice());
+    let function = args[1].as_slice();
+    let contract = env.caller_env.account_caller.clone();
+
+    let key = queue_key(height, &contract, function);
+    let value = kv_get(env, &key).unwrap_or_else(|| panic_any("schedule_not_found"));
+    kv_delete(env, &key);
+    if kv_increment(env, &count_key(height), -1) <= 0 {
+        kv_delete(env, &count_key(height));
+    }
+    kv_increment(env, &bcat(&[b"account:", &contract, b":balance:AMA"]), budget_of(&value));
+}
+
+//pops every callback due at this height, in key order
+pub fn take_due(env: &mut ApplyEnv) -> Vec<Scheduled> {
+    let height = env.caller_env.entry_height;
+    let prefix = queue_prefix(height);
+
+    let mut due = Vec::new();
+    let mut cursor: Vec<u8> = Vec::new();
+    while let Some((suffix, value)) = kv_get_next(env, &prefix, &cursor) {
+        cursor = suffix.clone();
+        if suffix.len() < 50 || suffix[48] != b':' { panic_any("invalid_schedule_entry") }
+
+        let term = decode(&value).unwrap_or_else(|_| panic_any("invalid_schedule_entry"));
+        let Term::PropList(pairs) = term else { panic_any("invalid_schedule_entry") };
+        let args = match pl_find_opt(&pairs, b"args") {
+            Some(Term::List(list)) => list.iter().map(|t| match t {
+                Term::Binary(b) => b.clone(),
+                _ => panic_any("invalid_schedule_entry")
+            }).collect(),
+            _ => panic_any("invalid_schedule_entry")
+        };
+
+        let key = bcat(&[&prefix, &suffix]);
+        due.push(Scheduled {
+            id: *blake3::hash(&key).as_bytes(),
+            contract: suffix[0..48].try_into().unwrap_or_else(|_| panic_any("invalid_schedule_entry")),
+            function: suffix[49..].to_vec(),
+            args,
+            budget: budget_of(&value),
+        });
+        kv_delete(env, &key);
+    }
+    //cancelled slots can leave a count behind even when nothing is due
+    kv_delete(env, &count_key(height));
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::consensus_apply::with_test_env;
+    use crate::consensus::consensus_apply::test_util::{args, as_caller, err, pk};
+
+    //a funded account with bytecode, acting as the caller
+    fn contract(env: &mut ApplyEnv, seed: u8, funds: i128) -> Vec<u8> {
+        let contract = pk(seed);
+        kv_put(env, &bcat(&[b"account:", &contract, b":attribute:bytecode"]), b"\0asm");
+        kv_increment(env, &bcat(&[b"account:", &contract, b":balance:AMA"]), funds);
+        as_caller(env, &contract);
+        contract
+    }
+
+    fn budget() -> Vec<u8> {
+        protocol::SCHEDULE_MIN_BUDGET.to_string().into_bytes()
+    }
+
+    #[test]
+    fn register_and_cancel_refunds() {
+        with_test_env(true, |env| {
+            let funds = protocol::SCHEDULE_MAX_BUDGET;
+            let c = contract(env, 1, funds);
+            call_register(env, args(&[b"5", b"tick", &budget(), b"a1"]));
+            assert_eq!(balance(env, &c, b"AMA"), funds - protocol::SCHEDULE_MIN_BUDGET);
+            assert_eq!(kv_get(env, &count_key(5)), Some(b"1".to_vec()));
+            assert_eq!(err(|| call_register(env, args(&[b"5", b"tick", &budget()]))), "schedule_already_registered");
+
+            call_cancel(env, args(&[b"5", b"tick"]));
+            assert_eq!(balance(env, &c, b"AMA"), funds);
+            assert_eq!(kv_get(env, &queue_key(5, &c, b"tick")), None);
+            //the last cancel at a height removes its count
+            assert_eq!(kv_get(env, &count_key(5)), None);
+            assert_eq!(err(|| call_cancel(env, args(&[b"5", b"tick"]))), "schedule_not_found");
+        });
+    }
+
+    #[test]
+    fn register_rejects() {
+        with_test_env(true, |env| {
+            as_caller(env, &pk(9));
+            assert_eq!(err(|| call_register(env, args(&[b"5", b"tick", &budget()]))), "caller_not_contract");
+
+            contract(env, 1, protocol::SCHEDULE_MIN_BUDGET);
+            let too_far = (1 + protocol::SCHEDULE_MAX_DELAY + 1).to_string().into_bytes();
+            assert_eq!(err(|| call_register(env, args(&[b"1", b"tick", &budget()]))), "schedule_height_not_in_future");
+            assert_eq!(err(|| call_register(env, args(&[&too_far, b"tick", &budget()]))), "schedule_height_too_far");
+            assert_eq!(err(|| call_register(env, args(&[b"5", b"", &budget()]))), "invalid_function");
+            let low = (protocol::SCHEDULE_MIN_BUDGET - 1).to_string().into_bytes();
+            let high = (protocol::SCHEDULE_MAX_BUDGET + 1).to_string().into_bytes();
+            assert_eq!(err(|| call_register(env, args(&[b"5", b"tick", &low]))), "invalid_budget");
+            assert_eq!(err(|| call_register(env, args(&[b"5", b"tick", &high]))), "invalid_budget");
+
+            let broke = contract(env, 2, protocol::SCHEDULE_MIN_BUDGET - 1);
+            assert_eq!(err(|| call_register(env, args(&[b"6", b"tick", &budget()]))), "insufficient_funds");
+            assert_eq!(balance(env, &broke, b"AMA"), protocol::SCHEDULE_MIN_BUDGET - 1);
+        });
+    }
+
+    #[test]
+    fn height_full() {
+        with_test_env(true, |env| {
+            for seed in 1..=protocol::SCHEDULE_MAX_PER_HEIGHT as u8 {
+                contract(env, seed, protocol::SCHEDULE_MIN_BUDGET);
+                call_register(env, args(&[b"5", b"tick", &budget()]));
+            }
+            contract(env, 100, protocol::SCHEDULE_MIN_BUDGET);
+            assert_eq!(err(|| call_register(env, args(&[b"5", b"tick", &budget()]))), "schedule_height_full");
+        });
+    }
+
+    #[test]
+    fn take_due_pops_in_key_order() {
+        with_test_env(true, |env| {
+            let a = contract(env, 1, protocol::SCHEDULE_MAX_BUDGET);
+            call_register(env, args(&[b"2", b"tock", &budget(), b"x"]));
+            call_register(env, args(&[b"2", b"tick", &budget()]));
+            call_register(env, args(&[b"3", b"tick", &budget()]));
+
+            env.caller_env.entry_height = 2;
+            let due = take_due(env);
+            let functions: Vec<&[u8]> = due.iter().map(|s| s.function.as_slice()).collect();
+            assert_eq!(functions, vec![b"tick".as_slice(), b"tock".as_slice()]);
+            assert!(due.iter().all(|s| s.contract.as_slice() == a.as_slice() && s.budget == protocol::SCHEDULE_MIN_BUDGET));
+            assert_eq!(due[1].args, vec![b"x".to_vec()]);
+            assert_eq!(kv_get(env, &count_key(2)), None);
+            assert!(take_due(env).is_empty());
+
+            //height 3 is untouched
+            env.caller_env.entry_height = 3;
+            assert_eq!(take_due(env).len(), 1);
+        });
+    }
+
+    #[test]
+    fn take_due_clears_count_left_by_cancels() {
+        with_test_env(true, |env| {
+            contract(env, 1, protocol::SCHEDULE_MAX_BUDGET);
+            call_register(env, args(&[b"2", b"tick", &budget()]));
+            //a stale count from an older node, before cancel cleaned up after itself
+            kv_put(env, &count_key(2), b"0");
+            kv_delete(env, &queue_key(2, &pk(1), b"tick"));
+
+            env.caller_env.entry_height = 2;
+            assert!(take_due(env).is_empty());
+            assert_eq!(kv_get(env, &count_key(2)), None);
+        });
+    }
+
+    #[test]
+    fn take_due_rejects_short_contract() {
+        with_test_env(true, |env| {
+            kv_put(env, &bcat(&[&queue_prefix(2), &[1u8; 10], b":tick"]), &encode(Term::PropList(vec![
+                (Term::Binary(b"budget".to_vec()), Term::VarInt(1)),
+                (Term::Binary(b"args".to_vec()), Term::List(vec![])),
+            ])));
+            env.caller_env.entry_height = 2;
+            assert_eq!(err(|| { take_due(env); }), "invalid_schedule_entry");
+        });
+    }
+}
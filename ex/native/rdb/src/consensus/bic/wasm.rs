@@ -1,6 +1,6 @@
 use crate::consensus::bic::protocol;
 use crate::consensus::consensus_apply::{ApplyEnv};
-use crate::consensus::consensus_kv::{kv_get, kv_get_prev, kv_get_next, kv_put, kv_exists, kv_delete, kv_set_bit, kv_increment, kv_get_prev_or_first};
+use crate::consensus::consensus_kv::{kv_get, kv_get_prev, kv_get_next, kv_put, kv_exists, kv_delete, kv_set_bit, kv_increment, kv_get_prev_or_first, kv_scan};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, SystemTime};
@@ -18,6 +18,7 @@ use wasmer::{
     RuntimeError
 };
 use wasmer_compiler_singlepass::Singlepass;
+use crate::consensus::bic::wasm_profile::Profiler;
 use wasmer_middlewares::{
     metering::{get_remaining_points, set_remaining_points, MeteringPoints},
     Metering,
@@ -54,6 +55,24 @@ fn set_return_value(applyenv: &mut ApplyEnv, return_value: Vec<u8>) {
     applyenv.caller_env.call_return_value = return_value
 }
 
+//host results go to the io region, never past the reserved end into contract data
+fn io_write(view: &MemoryView, offset: u64, data: &[u8]) {
+    let start = protocol::WASM_LAYOUT_IO + offset;
+    if start + data.len() as u64 > protocol::WASM_LAYOUT_RESERVED_END {
+        panic_any("exec_io_region_overflow")
+    }
+    view.write(start, data).unwrap_or_else(|_| panic_any("exec_memwrite"));
+}
+
+fn read_host_arg(view: &MemoryView, ptr: i32, len: i32) -> Vec<u8> {
+    if len < 0 || len as usize > protocol::WASM_MAX_PTR_LEN {
+        panic_any("exec_ptr_term_too_long")
+    }
+    let mut buf = vec![0u8; len as usize];
+    view.read(ptr as u64, &mut buf).unwrap_or_else(|_| panic_any("exec_invalid_ptr"));
+    buf
+}
+
 fn import_log_implementation(mut env: FunctionEnvMut<HostEnv>, ptr: i32, len: i32) {
     let (data, mut store) = env.data_and_store_mut();
     let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
@@ -177,8 +196,13 @@ fn import_call_implementation(mut env: FunctionEnvMut<HostEnv>, table_ptr: i32,
 
     let result = match crate::consensus::bls12_381::validate_public_key(contract.as_slice()) {
         false => {
-            crate::consensus::consensus_apply::call_bic(applyenv, contract, function, args, attached_symbol, attached_amount);
-            b"ok".to_vec()
+            match crate::consensus::consensus_apply::call_bic_view(applyenv, &contract, &function, &args) {
+                Some(result) => result,
+                None => {
+                    crate::consensus::consensus_apply::call_bic(applyenv, contract, function, args, attached_symbol, attached_amount);
+                    b"ok".to_vec()
+                }
+            }
         }
         true => {
             crate::consensus::consensus_apply::call_wasmvm(applyenv, contract, function, args, attached_symbol, attached_amount)
@@ -191,10 +215,81 @@ fn import_call_implementation(mut env: FunctionEnvMut<HostEnv>, table_ptr: i32,
     applyenv.caller_env.account_current = og_account_current;
 
     let view = data.memory.clone().view(&store);
-    view.write(10_000, &(result.len() as u32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
-    view.write(10_004, &result).unwrap_or_else(|_| panic_any("exec_memwrite"));
+    io_write(&view, 0, &(result.len() as u32).to_le_bytes());
+    io_write(&view, 4, &result);
+
+    Ok(protocol::WASM_LAYOUT_IO as i32)
+}
+
+fn import_emit_event_implementation(mut env: FunctionEnvMut<HostEnv>, topics_table_ptr: i32, data_ptr: i32, data_len: i32) -> Result<(), RuntimeError> {
+    let (data, mut store) = env.data_and_store_mut();
+    let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
+    let applyenv = unsafe { data.applyenv_ptr.as_mut() };
+    let view = data.memory.clone().view(&store);
+
+    if data_len < 0 || data_len as usize > protocol::EVENT_DATA_SIZE {
+        panic_any("exec_event_data_too_large")
+    }
+    if applyenv.events.len() >= protocol::EVENT_TOTAL_ELEMENTS {
+        panic_any("exec_events_total_elements_exceeded")
+    }
+
+    // Read topics table
+    let mut count_buf = [0u8; 4];
+    view.read(topics_table_ptr as u64, &mut count_buf).unwrap_or_else(|_| panic_any("exec_event_table_invalid_ptr"));
+    let topic_count = i32::from_le_bytes(count_buf) as usize;
+    if topic_count > protocol::EVENT_MAX_TOPICS { panic_any("exec_event_too_many_topics") }
+
+    let mut topics: Vec<Vec<u8>> = Vec::with_capacity(topic_count);
+    let mut size = data_len as usize;
+    for i in 0..topic_count {
+        let offset = (topics_table_ptr as u64) + 4 + (i as u64 * 8);
+        let mut row_buf = [0u8; 8];
+        view.read(offset, &mut row_buf).unwrap_or_else(|_| panic_any("exec_read_event_table_error"));
+        let topic_ptr = i32::from_le_bytes(row_buf[0..4].try_into().unwrap());
+        let topic_len = i32::from_le_bytes(row_buf[4..8].try_into().unwrap());
+
+        if topic_len <= 0 || topic_len as usize > protocol::EVENT_TOPIC_SIZE { panic_any("exec_event_invalid_topic") }
+
+        let mut topic = vec![0u8; topic_len as usize];
+        view.read(topic_ptr as u64, &mut topic).unwrap_or_else(|_| panic_any("exec_read_event_topic_error"));
+        size += topic.len();
+        topics.push(topic);
+    }
+
+    let mut event_data = vec![0u8; data_len as usize];
+    view.read(data_ptr as u64, &mut event_data).unwrap_or_else(|_| panic_any("exec_read_event_data_error"));
+
+    crate::consensus::consensus_kv::storage_budget_decr(applyenv, protocol::COST_PER_BYTE_HISTORICAL * size as i128);
+    set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
+
+    applyenv.events.push(crate::model::tx_receipt::TXEvent {
+        contract: applyenv.caller_env.account_current.clone(),
+        topics: topics,
+        data: event_data,
+    });
+    Ok(())
+}
+
+//moves funds out of the running contract without a Coin.transfer call frame
+fn import_transfer_implementation(mut env: FunctionEnvMut<HostEnv>, receiver_ptr: i32, receiver_len: i32,
+    amount_ptr: i32, amount_len: i32, symbol_ptr: i32, symbol_len: i32) -> Result<(), RuntimeError>
+{
+    let (data, mut store) = env.data_and_store_mut();
+    let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
+    let applyenv = unsafe { data.applyenv_ptr.as_mut() };
+    let view = data.memory.clone().view(&store);
 
-    Ok(10_000)
+    let receiver = read_host_arg(&view, receiver_ptr, receiver_len);
+    let amount = read_host_arg(&view, amount_ptr, amount_len);
+    let amount = std::str::from_utf8(&amount).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+    let symbol = read_host_arg(&view, symbol_ptr, symbol_len);
+
+    let sender = applyenv.caller_env.account_current.clone();
+    crate::consensus::bic::coin::transfer(applyenv, &sender, &receiver, amount, &symbol);
+
+    set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
+    Ok(())
 }
 
 fn build_prefixed_key(applyenv: &mut ApplyEnv, view: &MemoryView, ptr: i32, len: i32) -> Vec<u8> {
@@ -215,6 +310,10 @@ fn import_storage_kv_put_implementation(mut env: FunctionEnvMut<HostEnv>, key_pt
     if val_len as usize > protocol::WASM_MAX_PTR_LEN {
         panic_any("exec_ptr_term_too_long")
     }
+    //anything larger could never be returned by kv_get
+    if val_len as usize > protocol::WASM_MAX_KV_VALUE_SIZE {
+        panic_any("exec_kv_value_too_large")
+    }
 
     let view = data.memory.clone().view(&store);
     let key = build_prefixed_key(applyenv, &view, key_ptr, key_len);
@@ -246,10 +345,10 @@ fn import_storage_kv_increment_implementation(mut env: FunctionEnvMut<HostEnv>,
     let new_value = kv_increment(applyenv, &key, value_int128).to_string();
     let new_value = new_value.as_bytes();
 
-    view.write(10_000, &(new_value.len() as u32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
-    view.write(10_004, &new_value).unwrap_or_else(|_| panic_any("exec_memwrite"));
+    io_write(&view, 0, &(new_value.len() as u32).to_le_bytes());
+    io_write(&view, 4, &new_value);
 
-    Ok(10_000)
+    Ok(protocol::WASM_LAYOUT_IO as i32)
 }
 
 fn import_storage_kv_delete_implementation(mut env: FunctionEnvMut<HostEnv>, key_ptr: i32, key_len: i32) -> Result<(), RuntimeError> {
@@ -282,15 +381,15 @@ fn import_storage_kv_get_implementation(mut env: FunctionEnvMut<HostEnv>, ptr: i
     let key = build_prefixed_key(applyenv, &view, ptr, len);
     match kv_get(applyenv, &key) {
         None => {
-            view.write(10_000, &(-1i32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
+            io_write(&view, 0, &(-1i32).to_le_bytes());
         },
         Some(value) => {
-            view.write(10_000, &(value.len() as u32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
-            view.write(10_004, &value).unwrap_or_else(|_| panic_any("exec_memwrite"));
+            io_write(&view, 0, &(value.len() as u32).to_le_bytes());
+            io_write(&view, 4, &value);
         }
     }
     set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
-    Ok(10_000)
+    Ok(protocol::WASM_LAYOUT_IO as i32)
 }
 
 fn import_storage_kv_get_prev_implementation(mut env: FunctionEnvMut<HostEnv>, prefix_ptr: i32, prefix_len: i32, key_ptr: i32, key_len: i32) -> Result<i32, RuntimeError> {
@@ -312,18 +411,18 @@ fn import_storage_kv_get_prev_implementation(mut env: FunctionEnvMut<HostEnv>, p
 
     match kv_get_prev(applyenv, &prefix, &key) {
         None => {
-            view.write(10_000, &(-1i32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
+            io_write(&view, 0, &(-1i32).to_le_bytes());
         },
         Some((prev_key, value)) => {
-            view.write(10_000, &(prev_key.len() as u32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
-            view.write(10_000 + 4, &prev_key).unwrap_or_else(|_| panic_any("exec_memwrite"));
+            io_write(&view, 0, &(prev_key.len() as u32).to_le_bytes());
+            io_write(&view, 4, &prev_key);
 
-            view.write(10_000 + 4 + prev_key.len() as u64, &(value.len() as u32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
-            view.write(10_000 + 4 + prev_key.len() as u64 + 4, &value).unwrap_or_else(|_| panic_any("exec_memwrite"));
+            io_write(&view, 4 + prev_key.len() as u64, &(value.len() as u32).to_le_bytes());
+            io_write(&view, 4 + prev_key.len() as u64 + 4, &value);
         }
     }
     set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
-    Ok(10_000)
+    Ok(protocol::WASM_LAYOUT_IO as i32)
 }
 
 fn import_storage_kv_get_next_implementation(mut env: FunctionEnvMut<HostEnv>, prefix_ptr: i32, prefix_len: i32, key_ptr: i32, key_len: i32) -> Result<i32, RuntimeError> {
@@ -345,18 +444,56 @@ fn import_storage_kv_get_next_implementation(mut env: FunctionEnvMut<HostEnv>, p
 
     match kv_get_next(applyenv, &prefix, &key) {
         None => {
-            view.write(10_000, &(-1i32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
+            io_write(&view, 0, &(-1i32).to_le_bytes());
         },
         Some((next_key, value)) => {
-            view.write(10_000, &(next_key.len() as u32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
-            view.write(10_000 + 4, &next_key).unwrap_or_else(|_| panic_any("exec_memwrite"));
+            io_write(&view, 0, &(next_key.len() as u32).to_le_bytes());
+            io_write(&view, 4, &next_key);
 
-            view.write(10_000 + 4 + next_key.len() as u64, &(value.len() as u32).to_le_bytes()).unwrap_or_else(|_| panic_any("exec_memwrite"));
-            view.write(10_000 + 4 + next_key.len() as u64 + 4, &value).unwrap_or_else(|_| panic_any("exec_memwrite"));
+            io_write(&view, 4 + next_key.len() as u64, &(value.len() as u32).to_le_bytes());
+            io_write(&view, 4 + next_key.len() as u64 + 4, &value);
         }
     }
     set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
-    Ok(10_000)
+    Ok(protocol::WASM_LAYOUT_IO as i32)
+}
+
+fn import_storage_kv_scan_implementation(mut env: FunctionEnvMut<HostEnv>, prefix_ptr: i32, prefix_len: i32, start_ptr: i32, start_len: i32,
+    end_ptr: i32, end_len: i32, limit: i32, reverse: i32) -> Result<i32, RuntimeError>
+{
+    let (data, mut store) = env.data_and_store_mut();
+    let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
+    let applyenv = unsafe { data.applyenv_ptr.as_mut() };
+
+    if prefix_len as usize > protocol::WASM_MAX_PTR_LEN || start_len as usize > protocol::WASM_MAX_PTR_LEN || end_len as usize > protocol::WASM_MAX_PTR_LEN {
+        panic_any("exec_ptr_term_too_long")
+    }
+    if limit <= 0 {
+        panic_any("exec_scan_invalid_limit")
+    }
+
+    let view = data.memory.clone().view(&store);
+    let prefix = build_prefixed_key(applyenv, &view, prefix_ptr, prefix_len);
+    let mut start = vec![0u8; start_len as usize];
+    view.read(start_ptr as u64, &mut start).unwrap_or_else(|_| panic_any("exec_log_invalid_ptr"));
+    let mut end = vec![0u8; end_len as usize];
+    view.read(end_ptr as u64, &mut end).unwrap_or_else(|_| panic_any("exec_log_invalid_ptr"));
+
+    let pairs = kv_scan(applyenv, &prefix, &start, &end, limit as usize, reverse != 0);
+
+    // [count u32] ([key_len u32] [key] [value_len u32] [value])*
+    let mut packed = Vec::with_capacity(protocol::KV_SCAN_MAX_BYTES);
+    packed.extend_from_slice(&(pairs.len() as u32).to_le_bytes());
+    for (key, value) in pairs {
+        packed.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        packed.extend_from_slice(&key);
+        packed.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        packed.extend_from_slice(&value);
+    }
+    io_write(&view, 0, &packed);
+
+    set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
+    Ok(protocol::WASM_LAYOUT_IO as i32)
 }
 
 fn import_storage_kv_exists_implementation(mut env: FunctionEnvMut<HostEnv>, ptr: i32, len: i32) -> Result<i32, RuntimeError> {
@@ -379,6 +516,103 @@ fn import_storage_kv_exists_implementation(mut env: FunctionEnvMut<HostEnv>, ptr
     }
 }
 
+//Chain queries, readonly, reads are charged by the underlying kv calls
+fn import_coin_balance_implementation(mut env: FunctionEnvMut<HostEnv>, address_ptr: i32, address_len: i32, symbol_ptr: i32, symbol_len: i32) -> Result<i32, RuntimeError> {
+    let (data, mut store) = env.data_and_store_mut();
+    let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
+    let applyenv = unsafe { data.applyenv_ptr.as_mut() };
+    let view = data.memory.clone().view(&store);
+
+    let address = read_host_arg(&view, address_ptr, address_len);
+    let symbol = read_host_arg(&view, symbol_ptr, symbol_len);
+
+    let balance = crate::consensus::bic::coin::balance(applyenv, &address, &symbol).to_string().into_bytes();
+    io_write(&view, 0, &(balance.len() as u32).to_le_bytes());
+    io_write(&view, 4, &balance);
+
+    set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
+    Ok(protocol::WASM_LAYOUT_IO as i32)
+}
+
+fn import_coin_total_supply_implementation(mut env: FunctionEnvMut<HostEnv>, symbol_ptr: i32, symbol_len: i32) -> Result<i32, RuntimeError> {
+    let (data, mut store) = env.data_and_store_mut();
+    let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
+    let applyenv = unsafe { data.applyenv_ptr.as_mut() };
+    let view = data.memory.clone().view(&store);
+
+    let symbol = read_host_arg(&view, symbol_ptr, symbol_len);
+
+    let total_supply = crate::consensus::bic::coin::total_supply(applyenv, &symbol).to_string().into_bytes();
+    io_write(&view, 0, &(total_supply.len() as u32).to_le_bytes());
+    io_write(&view, 4, &total_supply);
+
+    set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
+    Ok(protocol::WASM_LAYOUT_IO as i32)
+}
+
+fn import_nft_balance_implementation(mut env: FunctionEnvMut<HostEnv>, address_ptr: i32, address_len: i32,
+    collection_ptr: i32, collection_len: i32, token_ptr: i32, token_len: i32) -> Result<i32, RuntimeError>
+{
+    let (data, mut store) = env.data_and_store_mut();
+    let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
+    let applyenv = unsafe { data.applyenv_ptr.as_mut() };
+    let view = data.memory.clone().view(&store);
+
+    let address = read_host_arg(&view, address_ptr, address_len);
+    let collection = read_host_arg(&view, collection_ptr, collection_len);
+    let token = read_host_arg(&view, token_ptr, token_len);
+
+    let balance = crate::consensus::bic::nft::balance(applyenv, &address, &collection, &token).to_string().into_bytes();
+    io_write(&view, 0, &(balance.len() as u32).to_le_bytes());
+    io_write(&view, 4, &balance);
+
+    set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
+    Ok(protocol::WASM_LAYOUT_IO as i32)
+}
+
+//recipient followed by the ascii royalty on price, empty when the collection has no royalty
+fn import_nft_royalty_implementation(mut env: FunctionEnvMut<HostEnv>, collection_ptr: i32, collection_len: i32,
+    price_ptr: i32, price_len: i32) -> Result<i32, RuntimeError>
+{
+    let (data, mut store) = env.data_and_store_mut();
+    let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
+    let applyenv = unsafe { data.applyenv_ptr.as_mut() };
+    let view = data.memory.clone().view(&store);
+
+    let collection = read_host_arg(&view, collection_ptr, collection_len);
+    let price = read_host_arg(&view, price_ptr, price_len);
+    let price = std::str::from_utf8(&price).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_amount"));
+
+    let result = match crate::consensus::bic::nft::royalty(applyenv, &collection) {
+        Some((recipient, bps)) => [recipient, crate::consensus::bic::nft::royalty_amount(price, bps).to_string().into_bytes()].concat(),
+        None => Vec::new()
+    };
+    io_write(&view, 0, &(result.len() as u32).to_le_bytes());
+    io_write(&view, 4, &result);
+
+    set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
+    Ok(protocol::WASM_LAYOUT_IO as i32)
+}
+
+//trainer pks concatenated, 48 bytes each
+fn import_epoch_trainers_implementation(mut env: FunctionEnvMut<HostEnv>, height: i64) -> Result<i32, RuntimeError> {
+    let (data, mut store) = env.data_and_store_mut();
+    let instance = data.instance.clone().unwrap_or_else(|| panic_any("exec_instance_not_injected"));
+    let applyenv = unsafe { data.applyenv_ptr.as_mut() };
+    let view = data.memory.clone().view(&store);
+
+    if height < 0 {
+        panic_any("exec_invalid_height")
+    }
+
+    let trainers = crate::consensus::bic::epoch::kv_get_trainers(applyenv, height as u64).concat();
+    io_write(&view, 0, &(trainers.len() as u32).to_le_bytes());
+    io_write(&view, 4, &trainers);
+
+    set_remaining_points(&mut store, &instance, applyenv.exec_left.max(0) as u64);
+    Ok(protocol::WASM_LAYOUT_IO as i32)
+}
+
 //AssemblyScript specific
 fn as_read_string(view: &MemoryView, ptr: i32) -> String {
     if ptr == 0 { return "null".to_string(); }
@@ -457,11 +691,30 @@ fn log_line(applyenv: &mut ApplyEnv, line: Vec<u8>) {
     applyenv.logs_size += len
 }
 
+//same limits whether the memory is imported from the host or defined by the module
+fn check_memory_type(m: &wasmer::wasmparser::MemoryType) -> Result<(), String> {
+    if m.memory64 || m.shared {
+        return Err("wasmparser_memory_type_not_allowed".to_string());
+    }
+    if m.initial > protocol::WASM_MEMORY_MAX_PAGES {
+        return Err("wasmparser_memory_initial_exceeds_limit".to_string());
+    }
+    if m.maximum.map_or(false, |max| max < m.initial) {
+        return Err("wasmparser_memory_max_below_initial".to_string());
+    }
+    Ok(())
+}
+
 pub fn check_module_limits(wasm_bytes: &[u8]) -> Result<(), String> {
     if wasm_bytes.len() > protocol::WASM_MAX_BINARY_SIZE {
         return Err("wasmparser_binary_size_exceeds_limit".to_string());
     }
 
+    let mut imported_globals: u32 = 0;
+    let mut global_inits: HashMap<u32, u64> = HashMap::new();
+    let mut global_names: HashMap<String, u32> = HashMap::new();
+    let mut data_ends: Vec<u64> = Vec::new();
+
     for payload in Parser::new(0).parse_all(wasm_bytes) {
         match payload.map_err(|e| e.to_string())? {
             Payload::FunctionSection(reader) => {
@@ -475,18 +728,54 @@ pub fn check_module_limits(wasm_bytes: &[u8]) -> Result<(), String> {
                 if count > protocol::WASM_MAX_GLOBALS {
                     return Err("wasmparser_global_count_exceeds_limit".to_string());
                 }
+                for (i, global) in reader.into_iter().enumerate() {
+                    let g = global.map_err(|e| e.to_string())?;
+                    if g.ty.content_type != wasmer::wasmparser::ValType::I32 { continue }
+                    if let Ok(Operator::I32Const { value }) = g.init_expr.get_binary_reader().read_operator() {
+                        global_inits.insert(imported_globals + i as u32, value as u32 as u64);
+                    }
+                }
             },
             Payload::ExportSection(reader) => {
                 let count = reader.count();
                 if count > protocol::WASM_MAX_EXPORTS {
                     return Err("wasmparser_export_count_exceeds_limit".to_string());
                 }
+                for export in reader {
+                    let e = export.map_err(|e| e.to_string())?;
+                    if e.kind == wasmer::wasmparser::ExternalKind::Global {
+                        global_names.insert(e.name.to_string(), e.index);
+                    }
+                }
+            },
+            Payload::CustomSection(reader) => {
+                if let wasmer::wasmparser::KnownCustom::Name(names) = reader.as_known() {
+                    for name in names {
+                        if let Ok(wasmer::wasmparser::Name::Global(map)) = name {
+                            for naming in map.into_iter().flatten() {
+                                global_names.entry(naming.name.to_string()).or_insert(naming.index);
+                            }
+                        }
+                    }
+                }
             },
             Payload::ImportSection(reader) => {
                 let count = reader.count();
                 if count > protocol::WASM_MAX_IMPORTS {
                     return Err("wasmparser_import_count_exceeds_limit".to_string());
                 }
+                for import in reader {
+                    let i = import.map_err(|e| e.to_string())?;
+                    if let wasmer::wasmparser::TypeRef::Global(_) = i.ty { imported_globals += 1 }
+                    if let wasmer::wasmparser::TypeRef::Memory(m) = i.ty {
+                        check_memory_type(&m)?;
+                    }
+                }
+            },
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    check_memory_type(&memory.map_err(|e| e.to_string())?)?;
+                }
             },
             Payload::CodeSectionStart { count, .. } => {
                 if count > protocol::WASM_MAX_FUNCTIONS {
@@ -494,29 +783,136 @@ pub fn check_module_limits(wasm_bytes: &[u8]) -> Result<(), String> {
                 }
             },
             Payload::DataSection(reader) => {
+                let memory_end = protocol::WASM_MEMORY_MAX_PAGES * protocol::WASM_PAGE_SIZE;
                 for data in reader {
                     let d = data.map_err(|e| e.to_string())?;
                     if let wasmer::wasmparser::DataKind::Active { offset_expr, .. } = d.kind {
+                         //offsets from a global are only known at instantiation, the runtime bounds check covers them
                          let mut r = offset_expr.get_binary_reader();
-                         if let Ok(wasmer::wasmparser::Operator::I32Const { value }) = r.read_operator() {
-                             if value >= 0 && value < 65536 {
-                                 return Err("wasmparser_first_65536_bytes_not_reserved".to_string());
-                             }
+                         let Ok(Operator::I32Const { value }) = r.read_operator() else { continue };
+                         let offset = value as u32 as u64;
+                         if offset < protocol::WASM_LAYOUT_RESERVED_END {
+                             return Err("wasmparser_first_65536_bytes_not_reserved".to_string());
+                         }
+                         if offset + d.data.len() as u64 > memory_end {
+                             return Err("wasmparser_data_exceeds_memory_limit".to_string());
                          }
+                         data_ends.push(offset + d.data.len() as u64);
                     }
                 }
             }
             _ => {}
         }
     }
+    check_stack_layout(&global_names, &global_inits, &data_ends)
+}
+
+//the stack must sit entirely above the host reserved region, it is only identifiable by name
+//its bottom is __stack_low when present, otherwise the end of the static data right below the top
+fn check_stack_layout(global_names: &HashMap<String, u32>, global_inits: &HashMap<u32, u64>, data_ends: &[u64]) -> Result<(), String> {
+    let named = |name: &str| global_names.get(name).and_then(|index| global_inits.get(index)).copied();
+    let Some(top) = named("__stack_pointer") else { return Ok(()) };
+    let bottom = named("__stack_low")
+        .or_else(|| named("__data_end").filter(|end| *end <= top))
+        .or_else(|| data_ends.iter().copied().filter(|end| *end <= top).max())
+        .unwrap_or(0);
+    if bottom < protocol::WASM_LAYOUT_RESERVED_END || top <= protocol::WASM_LAYOUT_RESERVED_END {
+        return Err("wasmparser_stack_overlaps_reserved".to_string());
+    }
     Ok(())
 }
 
+//wasm proposal and visitor name of an operator, straight from the wasmparser operator table
+fn operator_proposal(op: &Operator) -> (&'static str, &'static str) {
+    macro_rules! define_operator_proposal {
+        ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident ($($ann:tt)*))*) => {
+            match op {
+                $( Operator::$op { .. } => (stringify!($proposal), stringify!($visit)), )*
+                _ => ("unknown", "visit_unknown"),
+            }
+        }
+    }
+    wasmer::wasmparser::for_each_operator!(define_operator_proposal)
+}
+
+//every instruction that takes or produces an f32/f64, simd lanes are covered by the proposal list
+fn is_float_operator(op: &Operator) -> bool {
+    matches!(op,
+        Operator::F32Load { .. } | Operator::F64Load { .. } | Operator::F32Store { .. } | Operator::F64Store { .. } |
+        Operator::F32Const { .. } | Operator::F64Const { .. } |
+        Operator::F32Eq | Operator::F32Ne | Operator::F32Lt | Operator::F32Gt | Operator::F32Le | Operator::F32Ge |
+        Operator::F64Eq | Operator::F64Ne | Operator::F64Lt | Operator::F64Gt | Operator::F64Le | Operator::F64Ge |
+        Operator::F32Abs | Operator::F32Neg | Operator::F32Ceil | Operator::F32Floor | Operator::F32Trunc | Operator::F32Nearest |
+        Operator::F32Sqrt | Operator::F32Add | Operator::F32Sub | Operator::F32Mul | Operator::F32Div |
+        Operator::F32Min | Operator::F32Max | Operator::F32Copysign |
+        Operator::F64Abs | Operator::F64Neg | Operator::F64Ceil | Operator::F64Floor | Operator::F64Trunc | Operator::F64Nearest |
+        Operator::F64Sqrt | Operator::F64Add | Operator::F64Sub | Operator::F64Mul | Operator::F64Div |
+        Operator::F64Min | Operator::F64Max | Operator::F64Copysign |
+        Operator::I32TruncF32S | Operator::I32TruncF32U | Operator::I32TruncF64S | Operator::I32TruncF64U |
+        Operator::I64TruncF32S | Operator::I64TruncF32U | Operator::I64TruncF64S | Operator::I64TruncF64U |
+        Operator::F32ConvertI32S | Operator::F32ConvertI32U | Operator::F32ConvertI64S | Operator::F32ConvertI64U | Operator::F32DemoteF64 |
+        Operator::F64ConvertI32S | Operator::F64ConvertI32U | Operator::F64ConvertI64S | Operator::F64ConvertI64U | Operator::F64PromoteF32 |
+        Operator::I32ReinterpretF32 | Operator::I64ReinterpretF64 | Operator::F32ReinterpretI32 | Operator::F64ReinterpretI64 |
+        Operator::I32TruncSatF32S | Operator::I32TruncSatF32U | Operator::I32TruncSatF64S | Operator::I32TruncSatF64U |
+        Operator::I64TruncSatF32S | Operator::I64TruncSatF32U | Operator::I64TruncSatF64S | Operator::I64TruncSatF64U
+    )
+}
+
+fn operator_allowed(op: &Operator, proposal: &str) -> bool {
+    if !protocol::WASM_ALLOWED_PROPOSALS.contains(&proposal) { return false }
+    if !protocol::WASM_ALLOW_FLOATS && is_float_operator(op) { return false }
+    true
+}
+
+//static pass over every function body, returns the offending instructions
+pub fn audit_module(wasm_bytes: &[u8]) -> Result<Vec<String>, String> {
+    let mut findings = Vec::new();
+    let mut func_index: u32 = 0;
+
+    for payload in Parser::new(0).parse_all(wasm_bytes) {
+        match payload.map_err(|e| e.to_string())? {
+            //function indices start after the imported functions
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let i = import.map_err(|e| e.to_string())?;
+                    if let wasmer::wasmparser::TypeRef::Func(_) = i.ty { func_index += 1 }
+                }
+            },
+            Payload::CodeSectionEntry(body) => {
+                let mut reader = body.get_operators_reader().map_err(|e| e.to_string())?;
+                while !reader.eof() {
+                    let (op, offset) = reader.read_with_offset().map_err(|e| e.to_string())?;
+                    let (proposal, visit) = operator_proposal(&op);
+                    if operator_allowed(&op, proposal) { continue }
+                    if findings.len() >= protocol::WASM_MAX_AUDIT_FINDINGS { return Ok(findings) }
+                    let op_name = visit.strip_prefix("visit_").unwrap_or(visit);
+                    findings.push(format!("disallowed_instruction func={} offset={} op={} proposal={}", func_index, offset, op_name, proposal));
+                }
+                func_index += 1;
+            },
+            _ => {}
+        }
+    }
+    Ok(findings)
+}
+
 pub fn validate_contract(env: &mut ApplyEnv, wasm_bytes: &[u8]) {
     if let Err(e) = check_module_limits(wasm_bytes) {
         panic_any(e)
     }
 
+    let findings = audit_module(wasm_bytes).unwrap_or_else(|e| panic_any(e));
+    if !findings.is_empty() {
+        for line in findings {
+            log_line(env, line.into_bytes());
+        }
+        panic_any("wasmparser_disallowed_instruction")
+    }
+
+    if let Err(e) = crate::consensus::bic::abi::validate(wasm_bytes) {
+        panic_any(e)
+    }
+
     let engine = make_engine(env.exec_left.max(0) as u64);
     let mut store = Store::new(engine);
 
@@ -560,37 +956,57 @@ fn cost_function(operator: &Operator) -> u64 {
 }
 
 fn make_engine(exec_remaining: u64) -> Engine {
+    make_engine_profiled(exec_remaining, None)
+}
+
+//metering goes first so profiled and real calls are charged the same, the profiler skips what it injected
+fn make_engine_profiled(exec_remaining: u64, profiler: Option<Arc<Profiler<fn(&Operator) -> u64>>>) -> Engine {
     let metering = Arc::new(Metering::new(exec_remaining, cost_function));
 
     let mut compiler = Singlepass::default();
     compiler.canonicalize_nans(true);
     compiler.push_middleware(metering);
+    if let Some(profiler) = profiler {
+        compiler.push_middleware(profiler);
+    }
+
+    EngineBuilder::new(compiler)
+        .set_features(Some(wasm_features()))
+        .into()
+}
+
+//compiler features follow the proposals the deploy audit allows
+fn wasm_features() -> Features {
+    let allowed = |proposal: &str| protocol::WASM_ALLOWED_PROPOSALS.contains(&proposal);
 
     let mut features = Features::new();
-    features.threads(false);
-    features.reference_types(false);
-    features.simd(false);
+    features.threads(allowed("threads"));
+    features.reference_types(allowed("reference_types"));
+    features.simd(allowed("simd"));
+    features.tail_call(allowed("tail_call"));
+    features.exceptions(allowed("exceptions"));
+    features.bulk_memory(allowed("bulk_memory"));
     features.multi_value(false);
-    features.tail_call(false);
     features.module_linking(false);
+    features.multi_memory(false);
     features.memory64(false);
-    features.bulk_memory(true);
-
-    EngineBuilder::new(compiler)
-        .set_features(Some(features))
-        .into()
+    features
 }
 
 pub fn setup_wasm_instance(env: &mut ApplyEnv, module: &Module, store: &mut Store, readonly: bool, function_args: &[Vec<u8>]) -> (Instance, Vec<Value>) {
     // Setup Memory
-    let memory = Memory::new(store, MemoryType::new(Pages(2), Some(Pages(30)), false)).unwrap_or_else(|_| panic_any("exec_memory_alloc"));
+    let memory = Memory::new(store, memory_type(module)).unwrap_or_else(|_| panic_any("exec_memory_alloc"));
 
     let mut wasm_arg_ptrs: Vec<Value> = Vec::new();
     {
         let view = memory.view(store);
         inject_env_data(&view, env);
-        let mut current_offset: u64 = 10_000;
+        let mut current_offset: u64 = protocol::WASM_LAYOUT_IO;
         for arg_bytes in function_args {
+            // Args must fit the io region, data segments get initialized over anything past it
+            if current_offset + 4 + arg_bytes.len() as u64 > protocol::WASM_LAYOUT_RESERVED_END {
+                panic_any("exec_args_exceed_io_region")
+            }
             // Write the length + bytes
             let len = arg_bytes.len() as i32;
             view.write(current_offset, &len.to_le_bytes()).unwrap_or_else(|_| panic_any("exec_arg_len_write"));
@@ -622,6 +1038,8 @@ pub fn setup_wasm_instance(env: &mut ApplyEnv, module: &Module, store: &mut Stor
             "import_log" => Function::new_typed_with_env(store, &host_env, import_log_implementation),
             "import_return" => Function::new_typed_with_env(store, &host_env, import_return_implementation),
             "import_call" => Function::new_typed_with_env(store, &host_env, import_call_implementation),
+            "import_emit_event" => Function::new_typed_with_env(store, &host_env, import_emit_event_implementation),
+            "import_transfer" => Function::new_typed_with_env(store, &host_env, import_transfer_implementation),
 
             //Storage
             "import_kv_put" => Function::new_typed_with_env(store, &host_env, import_storage_kv_put_implementation),
@@ -632,6 +1050,14 @@ pub fn setup_wasm_instance(env: &mut ApplyEnv, module: &Module, store: &mut Stor
             "import_kv_exists" => Function::new_typed_with_env(store, &host_env, import_storage_kv_exists_implementation),
             "import_kv_get_prev" => Function::new_typed_with_env(store, &host_env, import_storage_kv_get_prev_implementation),
             "import_kv_get_next" => Function::new_typed_with_env(store, &host_env, import_storage_kv_get_next_implementation),
+            "import_kv_scan" => Function::new_typed_with_env(store, &host_env, import_storage_kv_scan_implementation),
+
+            //Chain queries
+            "import_coin_balance" => Function::new_typed_with_env(store, &host_env, import_coin_balance_implementation),
+            "import_coin_total_supply" => Function::new_typed_with_env(store, &host_env, import_coin_total_supply_implementation),
+            "import_nft_balance" => Function::new_typed_with_env(store, &host_env, import_nft_balance_implementation),
+            "import_nft_royalty" => Function::new_typed_with_env(store, &host_env, import_nft_royalty_implementation),
+            "import_epoch_trainers" => Function::new_typed_with_env(store, &host_env, import_epoch_trainers_implementation),
 
 
 
@@ -657,60 +1083,79 @@ pub fn setup_wasm_instance(env: &mut ApplyEnv, module: &Module, store: &mut Stor
     (instance, wasm_arg_ptrs)
 }
 
+//protocol limits, raised to what the module imports but never past the protocol max
+fn memory_type(module: &Module) -> MemoryType {
+    let mut initial = protocol::WASM_MEMORY_INITIAL_PAGES;
+    let mut maximum = protocol::WASM_MEMORY_MAX_PAGES;
+    for import in module.imports().memories() {
+        let ty = import.ty();
+        if ty.minimum.0 as u64 > protocol::WASM_MEMORY_MAX_PAGES { panic_any("exec_memory_initial_exceeds_max") }
+        initial = initial.max(ty.minimum.0 as u64);
+        if let Some(max) = ty.maximum {
+            maximum = maximum.min(max.0 as u64);
+        }
+    }
+    if maximum < initial { panic_any("exec_memory_max_below_initial") }
+    MemoryType::new(Pages(initial as u32), Some(Pages(maximum as u32)), false)
+}
+
 fn inject_env_data(view: &MemoryView, env: &ApplyEnv) {
     let mut w = |offset: u64, data: &[u8]| {
         view.write(offset, data).unwrap_or_else(|_| panic_any("exec_init_memwrite"))
     };
 
-    //Reserve first 1024 bytes
-    //Reserve first page 65536 bytes
-    w(1_100, &(env.caller_env.seed.len() as u32).to_le_bytes());
-    w(1_104, &env.caller_env.seed);
+    w(protocol::WASM_LAYOUT_VERSION_PTR, &protocol::WASM_LAYOUT_VERSION.to_le_bytes());
+    w(protocol::WASM_LAYOUT_SEED, &(env.caller_env.seed.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_SEED + 4, &env.caller_env.seed);
 
     // Entry
-    w(2_000, &env.caller_env.entry_slot.to_le_bytes());
-    w(2_010, &env.caller_env.entry_height.to_le_bytes());
-    w(2_020, &env.caller_env.entry_epoch.to_le_bytes());
+    w(protocol::WASM_LAYOUT_ENTRY_SLOT, &env.caller_env.entry_slot.to_le_bytes());
+    w(protocol::WASM_LAYOUT_ENTRY_HEIGHT, &env.caller_env.entry_height.to_le_bytes());
+    w(protocol::WASM_LAYOUT_ENTRY_EPOCH, &env.caller_env.entry_epoch.to_le_bytes());
     //
-    w(2_100, &(env.caller_env.entry_signer.len() as u32).to_le_bytes());
-    w(2_104, &env.caller_env.entry_signer);
-    w(2_200, &(env.caller_env.entry_prev_hash.len() as u32).to_le_bytes());
-    w(2_204, &env.caller_env.entry_prev_hash);
-    w(2_300, &(env.caller_env.entry_vr.len() as u32).to_le_bytes());
-    w(2_304, &env.caller_env.entry_vr);
-    w(2_400, &(env.caller_env.entry_dr.len() as u32).to_le_bytes());
-    w(2_404, &env.caller_env.entry_dr);
+    w(protocol::WASM_LAYOUT_ENTRY_SIGNER, &(env.caller_env.entry_signer.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ENTRY_SIGNER + 4, &env.caller_env.entry_signer);
+    w(protocol::WASM_LAYOUT_ENTRY_PREV_HASH, &(env.caller_env.entry_prev_hash.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ENTRY_PREV_HASH + 4, &env.caller_env.entry_prev_hash);
+    w(protocol::WASM_LAYOUT_ENTRY_VR, &(env.caller_env.entry_vr.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ENTRY_VR + 4, &env.caller_env.entry_vr);
+    w(protocol::WASM_LAYOUT_ENTRY_DR, &(env.caller_env.entry_dr.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ENTRY_DR + 4, &env.caller_env.entry_dr);
 
     // TX
-    w(3_000, &env.caller_env.tx_nonce.to_le_bytes());
+    w(protocol::WASM_LAYOUT_TX_NONCE, &env.caller_env.tx_nonce.to_le_bytes());
     //
-    w(3_100, &(env.caller_env.tx_signer.len() as u32).to_le_bytes());
-    w(3_104, &env.caller_env.tx_signer);
+    w(protocol::WASM_LAYOUT_TX_SIGNER, &(env.caller_env.tx_signer.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_TX_SIGNER + 4, &env.caller_env.tx_signer);
 
     // Accounts
-    w(4_000, &(env.caller_env.account_current.len() as u32).to_le_bytes());
-    w(4_004, &env.caller_env.account_current);
-    w(4_100, &(env.caller_env.account_caller.len() as u32).to_le_bytes());
-    w(4_104, &env.caller_env.account_caller);
-    w(4_200, &(env.caller_env.account_origin.len() as u32).to_le_bytes());
-    w(4_204, &env.caller_env.account_origin);
+    w(protocol::WASM_LAYOUT_ACCOUNT_CURRENT, &(env.caller_env.account_current.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ACCOUNT_CURRENT + 4, &env.caller_env.account_current);
+    w(protocol::WASM_LAYOUT_ACCOUNT_CALLER, &(env.caller_env.account_caller.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ACCOUNT_CALLER + 4, &env.caller_env.account_caller);
+    w(protocol::WASM_LAYOUT_ACCOUNT_ORIGIN, &(env.caller_env.account_origin.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ACCOUNT_ORIGIN + 4, &env.caller_env.account_origin);
 
     // Assets
-    w(5_000, &(env.caller_env.attached_symbol.len() as u32).to_le_bytes());
-    w(5_004, &env.caller_env.attached_symbol);
-    w(5_100, &(env.caller_env.attached_amount.len() as u32).to_le_bytes());
-    w(5_104, &env.caller_env.attached_amount);
+    w(protocol::WASM_LAYOUT_ATTACHED_SYMBOL, &(env.caller_env.attached_symbol.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ATTACHED_SYMBOL + 4, &env.caller_env.attached_symbol);
+    w(protocol::WASM_LAYOUT_ATTACHED_AMOUNT, &(env.caller_env.attached_amount.len() as u32).to_le_bytes());
+    w(protocol::WASM_LAYOUT_ATTACHED_AMOUNT + 4, &env.caller_env.attached_amount);
 }
 
 pub fn call_contract(env: &mut ApplyEnv, wasm_bytes: &[u8], function_name: String, function_args: Vec<Vec<u8>>) -> Vec<u8> {
     env.caller_env.call_return_value = Vec::new();
 
-    let engine = make_engine(env.exec_left.max(0) as u64);
+    let profiler = env.profile.as_ref().map(|_| Arc::new(Profiler::new(cost_function as fn(&Operator) -> u64)));
+    let engine = make_engine_profiled(env.exec_left.max(0) as u64, profiler.clone());
     let mut store = Store::new(engine);
 
     // Load Module (From Cache or Compile)
     let wasm_hash = Sha256::digest(wasm_bytes).to_vec();
-    let module = {
+    let module = if profiler.is_some() {
+        // Instrumented artifacts never enter the cache
+        Module::new(&store, wasm_bytes).unwrap_or_else(|_| panic_any("exec_invalid_module"))
+    } else {
         let mut cache = ARTIFACT_CACHE.lock().unwrap();
 
         //TODO: fix this to be more deterministic, as caches are node local
@@ -736,11 +1181,23 @@ pub fn call_contract(env: &mut ApplyEnv, wasm_bytes: &[u8], function_name: Strin
         log_line(env, e.to_string().into_bytes());
         panic_any("exec_function_not_found")
     });
+    if let Some(profile) = env.profile.as_mut() {
+        profile.stack.push(format!("{}.{}", bs58::encode(&env.caller_env.account_current).into_string(), function_name));
+    }
+
     let start = Instant::now();
     let call_result = entry_to_call.call(&mut store, &wasm_args);
     let duration = start.elapsed();
     //println!("call result {} {:?}", duration.as_millis(), call_result);
 
+    if let (Some(profile), Some(layout)) = (env.profile.as_mut(), profiler.as_ref().and_then(|p| p.layout())) {
+        profile.collect(&layout, &instance, &mut store);
+        profile.stack.pop();
+        if profile.stack.is_empty() {
+            profile.duration_us += duration.as_micros();
+        }
+    }
+
     let remaining = match get_remaining_points(&mut store, &instance) {
         MeteringPoints::Remaining(v) => v,
         MeteringPoints::Exhausted => {
@@ -759,3 +1216,60 @@ pub fn call_contract(env: &mut ApplyEnv, wasm_bytes: &[u8], function_name: Strin
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::consensus_apply::test_util::TICK_BOOM_WASM;
+
+    //(module (func (export "f") f32.const 0 drop))
+    const F32_CONST_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+        0x03, 0x02, 0x01, 0x00,
+        0x07, 0x05, 0x01, 0x01, 0x66, 0x00, 0x00,
+        0x0a, 0x0a, 0x01, 0x08, 0x00, 0x43, 0x00, 0x00, 0x00, 0x00, 0x1a, 0x0b,
+    ];
+
+    #[test]
+    fn defined_memory_checked_like_imported() {
+        //(module (memory 1))
+        assert_eq!(check_module_limits(&[0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01]), Ok(()));
+        //(module (memory 2 1))
+        assert_eq!(check_module_limits(&[0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x05, 0x04, 0x01, 0x01, 0x02, 0x01]),
+            Err("wasmparser_memory_max_below_initial".to_string()));
+        assert_eq!(check_module_limits(TICK_BOOM_WASM), Ok(()));
+    }
+
+    #[test]
+    fn data_offset_from_global_allowed() {
+        //(module (import "env" "g" (global i32)) (memory 1) (data (global.get 0) "a"))
+        assert_eq!(check_module_limits(&[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+            0x02, 0x0a, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x01, 0x67, 0x03, 0x7f, 0x00,
+            0x05, 0x03, 0x01, 0x00, 0x01,
+            0x0b, 0x07, 0x01, 0x00, 0x23, 0x00, 0x0b, 0x01, 0x61,
+        ]), Ok(()));
+    }
+
+    #[test]
+    fn audit_passes_integer_module() {
+        assert!(audit_module(TICK_BOOM_WASM).unwrap().is_empty());
+    }
+
+    #[test]
+    fn audit_rejects_floats() {
+        let findings = audit_module(F32_CONST_WASM).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].contains("op=f32_const"));
+    }
+
+    #[test]
+    fn float_operators_are_matched_by_variant() {
+        assert!(is_float_operator(&Operator::F64Add));
+        assert!(is_float_operator(&Operator::I32TruncSatF32S));
+        assert!(is_float_operator(&Operator::I64ReinterpretF64));
+        assert!(!is_float_operator(&Operator::I32Add));
+        assert!(!is_float_operator(&Operator::I64ExtendI32S));
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use wasmer::wasmparser::Operator;
+use wasmer::{
+    sys::{FunctionMiddleware, MiddlewareError, MiddlewareReaderState, ModuleMiddleware},
+    ExportIndex, GlobalInit, GlobalType, Instance, LocalFunctionIndex, Mutability, Store, Type, Value,
+};
+use wasmer_types::{entity::EntityRef, FunctionIndex, ImportIndex, ModuleInfo};
+use vecpak::{encode, Term};
+
+//Attributes metering points per function by keeping a cost and call counter global for every local function
+//and a call counter for every imported host function, only ever used for views and simulation
+//Runs after metering so its own counters are never charged, the checks metering injects are skipped
+//Metering owns the remaining points global, so every injected check is the run of operators from a
+//global.get of it up to the global.set storing it back; module code can never reference that global
+pub struct Profiler<F: Fn(&Operator) -> u64 + Send + Sync> {
+    cost_function: Arc<F>,
+    layout: Mutex<Option<ProfileLayout>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProfileLayout {
+    num_imported_functions: usize,
+    cost_globals: Vec<u32>,
+    calls_globals: Vec<u32>,
+    host_globals: Vec<u32>,
+    function_names: Vec<String>,
+    host_names: Vec<String>,
+    metering_global: Option<u32>,
+}
+
+impl<F: Fn(&Operator) -> u64 + Send + Sync> Profiler<F> {
+    pub fn new(cost_function: F) -> Self {
+        Self { cost_function: Arc::new(cost_function), layout: Mutex::new(None) }
+    }
+
+    pub fn layout(&self) -> Option<ProfileLayout> {
+        self.layout.lock().unwrap().clone()
+    }
+}
+
+impl<F: Fn(&Operator) -> u64 + Send + Sync> fmt::Debug for Profiler<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Profiler").field("layout", &self.layout).finish()
+    }
+}
+
+impl<F: Fn(&Operator) -> u64 + Send + Sync + 'static> ModuleMiddleware for Profiler<F> {
+    fn generate_function_middleware<'a>(&self, local_function_index: LocalFunctionIndex) -> Box<dyn FunctionMiddleware<'a> + 'a> {
+        let layout = self.layout.lock().unwrap().clone().unwrap_or_else(|| panic!("profiler_module_info_not_transformed"));
+        Box::new(FunctionProfiler {
+            cost_function: self.cost_function.clone(),
+            cost_global: layout.cost_globals[local_function_index.index()],
+            calls_global: layout.calls_globals[local_function_index.index()],
+            layout,
+            accumulated_cost: 0,
+            entered: false,
+            in_metering: false,
+        })
+    }
+
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) -> Result<(), MiddlewareError> {
+        let mut layout = self.layout.lock().unwrap();
+        if layout.is_some() {
+            return Err(MiddlewareError::new("profiler", "profiler_used_by_multiple_modules"));
+        }
+
+        let metering_global = match module_info.exports.get("wasmer_metering_remaining_points") {
+            Some(ExportIndex::Global(index)) => Some(index.as_u32()),
+            _ => None
+        };
+
+        let num_imported_functions = module_info.num_imported_functions;
+        let num_local_functions = module_info.functions.len() - num_imported_functions;
+
+        let mut cost_globals = Vec::with_capacity(num_local_functions);
+        let mut calls_globals = Vec::with_capacity(num_local_functions);
+        let mut function_names = Vec::with_capacity(num_local_functions);
+        for local in 0..num_local_functions {
+            cost_globals.push(push_counter(module_info, format!("profile_cost_{}", local)));
+            calls_globals.push(push_counter(module_info, format!("profile_calls_{}", local)));
+
+            let function_index = FunctionIndex::new(num_imported_functions + local);
+            let name = module_info.function_names.get(&function_index).cloned()
+                .or_else(|| module_info.exports.iter().find_map(|(name, export)| match export {
+                    ExportIndex::Function(idx) if *idx == function_index => Some(name.clone()),
+                    _ => None
+                }))
+                .unwrap_or_else(|| format!("func_{}", num_imported_functions + local));
+            function_names.push(name);
+        }
+
+        let mut host_names = vec![String::new(); num_imported_functions];
+        for (key, import) in module_info.imports.iter() {
+            if let ImportIndex::Function(idx) = import {
+                host_names[idx.index()] = key.field.clone();
+            }
+        }
+        let mut host_globals = Vec::with_capacity(num_imported_functions);
+        for host in 0..num_imported_functions {
+            host_globals.push(push_counter(module_info, format!("profile_host_{}", host)));
+        }
+
+        *layout = Some(ProfileLayout { num_imported_functions, cost_globals, calls_globals, host_globals, function_names, host_names, metering_global });
+        Ok(())
+    }
+}
+
+fn push_counter(module_info: &mut ModuleInfo, export_name: String) -> u32 {
+    let index = module_info.globals.push(GlobalType::new(Type::I64, Mutability::Var));
+    module_info.global_initializers.push(GlobalInit::I64Const(0));
+    module_info.exports.insert(export_name, ExportIndex::Global(index));
+    index.as_u32()
+}
+
+struct FunctionProfiler<F: Fn(&Operator) -> u64 + Send + Sync> {
+    cost_function: Arc<F>,
+    layout: ProfileLayout,
+    cost_global: u32,
+    calls_global: u32,
+    accumulated_cost: u64,
+    entered: bool,
+    in_metering: bool,
+}
+
+impl<F: Fn(&Operator) -> u64 + Send + Sync> fmt::Debug for FunctionProfiler<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionProfiler").field("cost_global", &self.cost_global).finish()
+    }
+}
+
+fn counter_add<'a>(state: &mut MiddlewareReaderState<'a>, global_index: u32, value: i64) {
+    state.extend(&[
+        Operator::GlobalGet { global_index },
+        Operator::I64Const { value },
+        Operator::I64Add,
+        Operator::GlobalSet { global_index },
+    ]);
+}
+
+impl<'a, F: Fn(&Operator) -> u64 + Send + Sync> FunctionMiddleware<'a> for FunctionProfiler<F> {
+    fn feed(&mut self, operator: Operator<'a>, state: &mut MiddlewareReaderState<'a>) -> Result<(), MiddlewareError> {
+        match operator {
+            Operator::GlobalGet { global_index } if Some(global_index) == self.layout.metering_global => self.in_metering = true,
+            Operator::GlobalSet { global_index } if self.in_metering && Some(global_index) == self.layout.metering_global => {
+                self.in_metering = false;
+                state.push_operator(operator);
+                return Ok(())
+            }
+            _ => {}
+        }
+        if self.in_metering {
+            state.push_operator(operator);
+            return Ok(())
+        }
+
+        if !self.entered {
+            self.entered = true;
+            counter_add(state, self.calls_global, 1);
+        }
+
+        //same cost and accounting points as metering so the totals line up
+        self.accumulated_cost += (self.cost_function)(&operator);
+        match operator {
+            Operator::Loop { .. }
+            | Operator::End
+            | Operator::Else
+            | Operator::Br { .. }
+            | Operator::BrTable { .. }
+            | Operator::BrIf { .. }
+            | Operator::Call { .. }
+            | Operator::CallIndirect { .. }
+            | Operator::Return => {
+                if self.accumulated_cost > 0 {
+                    counter_add(state, self.cost_global, self.accumulated_cost as i64);
+                    self.accumulated_cost = 0;
+                }
+            }
+            _ => {}
+        }
+
+        if let Operator::Call { function_index } = operator {
+            if (function_index as usize) < self.layout.num_imported_functions {
+                counter_add(state, self.layout.host_globals[function_index as usize], 1);
+            }
+        }
+
+        state.push_operator(operator);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    //contract.function frames currently executing, outermost first
+    pub stack: Vec<String>,
+    //folded stack -> (self cost, calls)
+    pub functions: Vec<(String, u64, u64)>,
+    pub host_calls: HashMap<String, u64>,
+    pub kv_read_bytes: u64,
+    pub kv_write_bytes: u64,
+    pub duration_us: u128,
+}
+
+impl Profile {
+    pub fn collect(&mut self, layout: &ProfileLayout, instance: &Instance, store: &mut Store) {
+        let read = |store: &mut Store, name: String| -> u64 {
+            match instance.exports.get_global(&name).map(|g| g.get(store)) {
+                Ok(Value::I64(v)) => v as u64,
+                _ => 0
+            }
+        };
+
+        let frame = self.stack.join(";");
+        for (local, name) in layout.function_names.iter().enumerate() {
+            let cost = read(store, format!("profile_cost_{}", local));
+            let calls = read(store, format!("profile_calls_{}", local));
+            if calls == 0 { continue }
+            self.functions.push((format!("{};{}", frame, name), cost, calls));
+        }
+        for (host, name) in layout.host_names.iter().enumerate() {
+            let calls = read(store, format!("profile_host_{}", host));
+            if calls == 0 { continue }
+            *self.host_calls.entry(name.clone()).or_insert(0) += calls;
+        }
+    }
+
+    //folded stacks are one "frame;frame;func cost" line each, ready for flamegraph tooling
+    pub fn report(&self) -> Vec<u8> {
+        let folded = self.functions.iter()
+            .map(|(stack, cost, _)| format!("{} {}", stack, cost))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut host_calls: Vec<(&String, &u64)> = self.host_calls.iter().collect();
+        host_calls.sort();
+
+        encode(Term::PropList(vec![
+            (Term::Binary(b"exec_used".to_vec()), Term::VarInt(self.functions.iter().map(|(_, cost, _)| *cost as i128).sum())),
+            (Term::Binary(b"duration_us".to_vec()), Term::VarInt(self.duration_us as i128)),
+            (Term::Binary(b"kv_read_bytes".to_vec()), Term::VarInt(self.kv_read_bytes as i128)),
+            (Term::Binary(b"kv_write_bytes".to_vec()), Term::VarInt(self.kv_write_bytes as i128)),
+            (Term::Binary(b"functions".to_vec()), Term::List(self.functions.iter().map(|(stack, cost, calls)| Term::PropList(vec![
+                (Term::Binary(b"stack".to_vec()), Term::Binary(stack.clone().into_bytes())),
+                (Term::Binary(b"self_cost".to_vec()), Term::VarInt(*cost as i128)),
+                (Term::Binary(b"calls".to_vec()), Term::VarInt(*calls as i128)),
+            ])).collect())),
+            (Term::Binary(b"host_calls".to_vec()), Term::List(host_calls.iter().map(|(name, calls)| Term::PropList(vec![
+                (Term::Binary(b"name".to_vec()), Term::Binary(name.as_bytes().to_vec())),
+                (Term::Binary(b"calls".to_vec()), Term::VarInt(**calls as i128)),
+            ])).collect())),
+            (Term::Binary(b"folded".to_vec()), Term::Binary(folded.into_bytes())),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::consensus_apply::with_test_env;
+    use crate::consensus::consensus_apply::test_util::TICK_BOOM_WASM;
+    use crate::model::_codec::pl_find_opt;
+
+    //any injected metering operator the profiler fails to skip shows up as extra self cost
+    #[test]
+    fn profile_matches_metering() {
+        with_test_env(true, |env| {
+            env.exec_max = 1_000_000;
+            env.exec_left = 1_000_000;
+            env.profile = Some(Profile::default());
+            crate::consensus::bic::wasm::call_contract(env, TICK_BOOM_WASM, "tick".to_string(), Vec::new());
+            let used = (env.exec_max - env.exec_left) as u64;
+            let profile = env.profile.take().unwrap();
+
+            assert!(used > 0);
+            assert_eq!(profile.functions.len(), 1);
+            let (stack, cost, calls) = &profile.functions[0];
+            assert!(stack.ends_with(".tick;tick"));
+            assert_eq!((*cost, *calls), (used, 1));
+            assert!(profile.stack.is_empty());
+
+            let report = vecpak::decode(&profile.report()).unwrap();
+            let Term::PropList(report) = report else { panic!("not a proplist") };
+            assert!(matches!(pl_find_opt(&report, b"exec_used"), Some(Term::VarInt(v)) if *v == used as i128));
+        });
+    }
+
+    #[test]
+    fn report_sorts_host_calls() {
+        let mut profile = Profile::default();
+        profile.functions.push(("a.f;f".to_string(), 5, 2));
+        profile.host_calls.insert("import_kv_put".to_string(), 3);
+        profile.host_calls.insert("import_kv_get".to_string(), 1);
+
+        let Term::PropList(report) = vecpak::decode(&profile.report()).unwrap() else { panic!("not a proplist") };
+        assert!(matches!(pl_find_opt(&report, b"folded"), Some(Term::Binary(b)) if b.as_slice() == b"a.f;f 5"));
+        let Some(Term::List(host_calls)) = pl_find_opt(&report, b"host_calls") else { panic!("no host_calls") };
+        let names: Vec<&[u8]> = host_calls.iter().map(|t| match t {
+            Term::PropList(pairs) => crate::model::_codec::pl_get_bytes_opt(pairs, b"name").unwrap(),
+            _ => panic!("not a proplist"),
+        }).collect();
+        assert_eq!(names, vec![b"import_kv_get".as_slice(), b"import_kv_put".as_slice()]);
+    }
+}
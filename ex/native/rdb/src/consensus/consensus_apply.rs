@@ -5,7 +5,7 @@ use crate::{
 use crate::consensus::bic::protocol;
 use crate::consensus::{bintree, consensus_kv};
 use crate::consensus::consensus_muts;
-use crate::model::tx_receipt::TXReceipt;
+use crate::model::tx_receipt::{TXReceipt, TXEvent};
 use std::clone;
 use std::collections::HashMap;
 use std::panic::panic_any;
@@ -28,6 +28,7 @@ pub struct CallerEnv {
     pub tx_nonce: u64,
     pub tx_hash: [u8; 32],
     pub account_origin: Vec<u8>,
+    pub account_payer: Vec<u8>,
     pub account_caller: Vec<u8>,
     pub account_current: Vec<u8>,
     pub attached_symbol: Vec<u8>,
@@ -61,6 +62,7 @@ pub fn make_caller_env(
         tx_nonce: 0,
         tx_hash: [0u8; 32],
         account_origin: Vec::new(),
+        account_payer: Vec::new(),
         account_caller: Vec::new(),
         account_current: Vec::new(),
         attached_symbol: Vec::new(),
@@ -89,12 +91,15 @@ pub struct ApplyEnv<'db> {
     pub exec_max: i128,
     pub storage_left: i128,
     pub storage_max: i128,
+    pub budget_shared: bool,
     pub receipts: Vec<TXReceipt>,
     pub logs: Vec<Vec<u8>>,
     pub logs_size: usize,
+    pub events: Vec<TXEvent>,
     pub testnet: bool,
     pub testnet_peddlebikes: Vec<Vec<u8>>,
     pub readonly: bool,
+    pub profile: Option<crate::consensus::bic::wasm_profile::Profile>,
 }
 
 impl<'db> ApplyEnv<'db> {
@@ -137,20 +142,24 @@ pub fn make_apply_env<'db>(db: &'db TransactionDB<MultiThreaded>, txn: Transacti
         exec_max: protocol::AMA_10_CENT,
         storage_left: 0,
         storage_max: protocol::AMA_1_DOLLAR,
+        budget_shared: false,
         receipts: Vec::new(),
         logs: Vec::new(),
         logs_size: 0,
+        events: Vec::new(),
         testnet: testnet,
         testnet_peddlebikes: testnet_peddlebikes,
         readonly: false,
+        profile: None,
     }
 }
 
-pub fn set_apply_env_tx<'db>(env: &mut ApplyEnv<'db>, tx_hash: &[u8; 32], tx_signer: &[u8; 48], tx_nonce: u64) {
+pub fn set_apply_env_tx<'db>(env: &mut ApplyEnv<'db>, tx_hash: &[u8; 32], tx_signer: &[u8; 48], tx_nonce: u64, tx_payer: &[u8]) {
     env.caller_env.tx_hash = *tx_hash;
     env.caller_env.tx_nonce = tx_nonce;
     env.caller_env.tx_signer = *tx_signer;
     env.caller_env.account_origin = tx_signer.to_vec();
+    env.caller_env.account_payer = tx_payer.to_vec();
 }
 
 pub fn apply_entry<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, txn: Transaction<'db, TransactionDB<MultiThreaded>>,
@@ -175,56 +184,70 @@ pub fn apply_entry<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, txn: Transact
         entry_epoch, entry_vr, entry_vr_b3, entry_dr,
         testnet, testnet_peddlebikes);
 
-    call_txs_pre_upfront_cost(&mut applyenv, &entry.txs);
+    let reserved = call_txs_pre_upfront_cost(&mut applyenv, &entry.txs);
 
     for (i, txu) in entry.txs.clone().into_iter().enumerate() {
         let tx_historical_cost = crate::consensus::bic::protocol::tx_historical_cost(&txu);
+        let (tx_exec_limit, tx_storage_limit) = reserved[i].unwrap_or((0, 0));
 
         let tx_hash = txu.hash.as_slice().try_into().unwrap_or_else(|_| panic!("tx_hash_len_wrong"));
         let tx_signer = txu.tx.signer.as_slice().try_into().unwrap_or_else(|_| panic!("tx_signer_len_wrong"));
         let tx_nonce = txu.tx.nonce;
-        let action = txu.tx.action;
+        let tx_payer = txu.tx.payer().to_vec();
+        let actions = txu.tx.actions;
+        let multi_action = actions.len() > 1;
 
         applyenv.caller_env.tx_index = i as u64;
         applyenv.caller_env.tx_hash = tx_hash;
         applyenv.caller_env.tx_signer = tx_signer;
         applyenv.caller_env.tx_nonce = tx_nonce;
         applyenv.caller_env.account_origin = tx_signer.to_vec();
-        applyenv.caller_env.account_caller = tx_signer.to_vec();
+        applyenv.caller_env.account_payer = tx_payer;
 
-        //let op = action.map_get(crate::atoms::op()).unwrap().decode::<rustler::Binary>().unwrap().as_slice();
-        let contract = action.contract;
-        let function = action.function;
-        let args = action.args;
-        let attached_symbol = action.attached_symbol.clone();
-        let attached_amount = action.attached_amount.clone();
-
-        applyenv.caller_env.call_counter += 1;
-        applyenv.caller_env.account_current = contract.to_vec();
         applyenv.muts = Vec::new();
         applyenv.muts_rev = Vec::new();
         applyenv.logs = Vec::new();
         applyenv.logs_size = 0;
+        applyenv.events = Vec::new();
         applyenv.exec_track = true;
-        applyenv.exec_left = protocol::AMA_10_CENT;
-        applyenv.exec_max = protocol::AMA_10_CENT;
-        applyenv.storage_left = protocol::AMA_1_DOLLAR;
-        applyenv.storage_max = protocol::AMA_1_DOLLAR;
+        applyenv.exec_left = tx_exec_limit;
+        applyenv.exec_max = tx_exec_limit;
+        applyenv.storage_left = tx_storage_limit;
+        applyenv.storage_max = tx_storage_limit;
 
+        //every action shares the one deposit, any failure reverts the whole tx
         std::panic::set_hook(Box::new(|_| {}));
         let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            match consensus::bls12_381::validate_public_key(contract.as_slice()) {
-                false => {
-                    //println!("{:?}->{:?} {:?} {:?}", String::from_utf8_lossy(&contract), String::from_utf8_lossy(&function), attached_amount, attached_symbol);
-                    call_bic(&mut applyenv, contract, function, args, attached_symbol, attached_amount);
-                    b"ok".to_vec()
-                }
-                true => {
-                    //println!("{:?}->{:?} {:?} {:?}", bs58::encode(&contract).into_string(), String::from_utf8_lossy(&function), attached_amount, attached_symbol);
-                    let result = call_wasmvm(&mut applyenv, contract, function, args, attached_symbol, attached_amount);
-                    result
-                }
+            if reserved[i].is_none() { panic_any("insufficient_funds_for_reservation") }
+            if actions.is_empty() { panic_any("no_actions") }
+            if actions.len() > protocol::TX_MAX_ACTIONS { panic_any("too_many_actions") }
+            let mut results = Vec::with_capacity(actions.len());
+            for action in actions {
+                //let op = action.map_get(crate::atoms::op()).unwrap().decode::<rustler::Binary>().unwrap().as_slice();
+                let contract = action.contract;
+                let function = action.function;
+                let args = action.args;
+                let attached_symbol = action.attached_symbol;
+                let attached_amount = action.attached_amount;
+
+                applyenv.caller_env.call_counter += 1;
+                applyenv.caller_env.account_caller = tx_signer.to_vec();
+                applyenv.caller_env.account_current = contract.to_vec();
+
+                let result = match consensus::bls12_381::validate_public_key(contract.as_slice()) {
+                    false => {
+                        //println!("{:?}->{:?} {:?} {:?}", String::from_utf8_lossy(&contract), String::from_utf8_lossy(&function), attached_amount, attached_symbol);
+                        call_bic(&mut applyenv, contract, function, args, attached_symbol, attached_amount);
+                        b"ok".to_vec()
+                    }
+                    true => {
+                        //println!("{:?}->{:?} {:?} {:?}", bs58::encode(&contract).into_string(), String::from_utf8_lossy(&function), attached_amount, attached_symbol);
+                        call_wasmvm(&mut applyenv, contract, function, args, attached_symbol, attached_amount)
+                    }
+                };
+                results.push(result);
             }
+            results
         }));
 
         applyenv.exec_track = false;
@@ -232,7 +255,9 @@ pub fn apply_entry<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, txn: Transact
         let exec_cost_total = ((tx_historical_cost + (applyenv.exec_max - applyenv.exec_left) + (applyenv.storage_max - applyenv.storage_left)) as u64).to_string();
 
         match res {
-            Ok(result) => {
+            Ok(mut results) => {
+                let result = results.last().cloned().unwrap_or_default();
+                if !multi_action { results = Vec::new() }
                 applyenv.muts_final.append(&mut applyenv.muts);
                 applyenv.muts_final_rev.append(&mut applyenv.muts_rev);
                 refund_exec_storage_deposit(&mut applyenv);
@@ -264,6 +289,8 @@ pub fn apply_entry<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, txn: Transact
                     result: result.into(),
                     exec_used: exec_cost_total.clone().into(),
                     logs: applyenv.logs.clone(),
+                    events: applyenv.events.clone(),
+                    results: results,
                 };
                 applyenv.receipts.push(receipt);
             }
@@ -279,6 +306,8 @@ pub fn apply_entry<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, txn: Transact
                         result: s.to_string().into(),
                         exec_used: exec_cost_total.clone().into(),
                         logs: applyenv.logs.clone(),
+                        events: Vec::new(),
+                        results: Vec::new(),
                     };
                     applyenv.receipts.push(receipt);
                 } else {
@@ -288,6 +317,8 @@ pub fn apply_entry<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, txn: Transact
                         result: b"unknown".into(),
                         exec_used: exec_cost_total.clone().into(),
                         logs: applyenv.logs.clone(),
+                        events: Vec::new(),
+                        results: Vec::new(),
                     };
                     applyenv.receipts.push(receipt);
                 }
@@ -297,7 +328,7 @@ pub fn apply_entry<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, txn: Transact
 
     call_exit(&mut applyenv);
 
-    let root_receipts = root_receipts(entry.txs.clone(), applyenv.receipts.clone());
+    let root_receipts = root_receipts(applyenv.receipts.clone());
     let root_contractstate = update_and_root_contractstate(&mut applyenv);
     applyenv.into_parts(root_receipts, root_contractstate)
 
@@ -308,7 +339,16 @@ pub fn apply_entry<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, txn: Transact
 
 pub fn contract_view<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, entry: crate::model::entry::Entry, view_pk: Vec<u8>,
     contract: Vec<u8>, function: Vec<u8>, args: Vec<Vec<u8>>, testnet: bool,
-) -> (bool, Vec<u8>, Vec<Vec<u8>>) {
+) -> (bool, Vec<u8>, Vec<Vec<u8>>, bool) {
+    let (success, result, logs, typed, _report) = contract_view_profiled(db, entry, view_pk, contract, function, args, testnet, false);
+    (success, result, logs, typed)
+}
+
+//same as contract_view, optionally instrumenting wasm calls to return a per function profiling report
+//typed is set when the function is described by the contract abi and the result is a vecpak term
+pub fn contract_view_profiled<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, entry: crate::model::entry::Entry, view_pk: Vec<u8>,
+    contract: Vec<u8>, function: Vec<u8>, args: Vec<Vec<u8>>, testnet: bool, profile: bool,
+) -> (bool, Vec<u8>, Vec<Vec<u8>>, bool, Option<Vec<u8>>) {
     let cf_h = db.cf_handle("contractstate").unwrap();
     let cf2_h = db.cf_handle("contractstate").unwrap();
     let cf_tree_h = db.cf_handle("contractstate_tree").unwrap();
@@ -335,35 +375,55 @@ pub fn contract_view<'db, 'a>(db: &'db TransactionDB<MultiThreaded>, entry: crat
     applyenv.caller_env.tx_signer = view_pk;
     applyenv.caller_env.account_current = contract.to_vec();
     applyenv.caller_env.account_origin = view_pk.to_vec();
+    applyenv.caller_env.account_payer = view_pk.to_vec();
     applyenv.caller_env.account_caller = view_pk.to_vec();
     applyenv.exec_left = protocol::AMA_10_CENT;
     applyenv.storage_left = protocol::AMA_1_DOLLAR;
+    if profile {
+        applyenv.profile = Some(crate::consensus::bic::wasm_profile::Profile::default());
+    }
 
     std::panic::set_hook(Box::new(|_| {}));
     let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
         match consensus::bls12_381::validate_public_key(contract.as_slice()) {
             false => {
-                call_bic(&mut applyenv, contract, function, args, None, None);
-                b"ok".to_vec()
+                match call_bic_view(&mut applyenv, &contract, &function, &args) {
+                    //builtin views always answer in vecpak
+                    Some(result) => (result, true),
+                    None => {
+                        call_bic(&mut applyenv, contract, function, args, None, None);
+                        (b"ok".to_vec(), false)
+                    }
+                }
             }
             true => {
-                let result = call_wasmvm(&mut applyenv, contract, function, args, None, None);
-                result
+                let abi = consensus::bic::abi::lookup(&mut applyenv, &contract);
+                let abi_function = std::str::from_utf8(&function).ok().and_then(|name| abi.as_ref()?.function(name).cloned());
+                match abi_function {
+                    None => (call_wasmvm(&mut applyenv, contract, function, args, None, None), false),
+                    Some(abi_function) => {
+                        let args = consensus::bic::abi::coerce_args(&abi_function, &args);
+                        let result = call_wasmvm(&mut applyenv, contract, function, args, None, None);
+                        consensus::bic::abi::check_result(&abi_function, &result);
+                        (result, abi_function.returns.is_some())
+                    }
+                }
             }
         }
     }));
 
     applyenv.txn.rollback();
 
+    let report = applyenv.profile.as_ref().map(|p| p.report());
     match res {
-        Ok(result) => {
-            (true, result.into(), applyenv.logs.clone())
+        Ok((result, typed)) => {
+            (true, result.into(), applyenv.logs.clone(), typed, report)
         }
         Err(payload) => {
             if let Some(&s) = payload.downcast_ref::<&'static str>() {
-                (false, s.to_string().into(), applyenv.logs.clone())
+                (false, s.to_string().into(), applyenv.logs.clone(), false, report)
             } else {
-                (false, b"unknown".into(), applyenv.logs.clone())
+                (false, b"unknown".into(), applyenv.logs.clone(), false, report)
             }
         }
     }
@@ -466,9 +526,10 @@ fn update_and_root_contractstate(applyenv: &mut ApplyEnv) -> [u8; 32] {
     contractstate_root
 }
 
-fn root_receipts(txus: Vec<crate::model::tx::TXU>, receipts: Vec<TXReceipt>) -> [u8; 32] {
+//one receipt per tx followed by one per scheduled callback
+fn root_receipts(receipts: Vec<TXReceipt>) -> [u8; 32] {
     use sha2::{Sha256, Digest};
-    let count = txus.len();
+    let count = receipts.len();
     let mut kvs = Vec::with_capacity((count * 4) + 1);
 
     kvs.push(bintree::Op::Insert(None, b"count".to_vec(), (count as u32).to_be_bytes().to_vec()));
@@ -490,6 +551,31 @@ fn root_receipts(txus: Vec<crate::model::tx::TXU>, receipts: Vec<TXReceipt>) ->
         kvs.push(bintree::Op::Insert(Some(b"success".to_vec()), receipt.txid.to_vec(), success_bytes));
         kvs.push(bintree::Op::Insert(Some(b"result".to_vec()), receipt.txid.to_vec(), receipt.result));
         kvs.push(bintree::Op::Insert(Some(b"logs".to_vec()), receipt.txid.to_vec(), log_hash.to_vec()));
+
+        if !receipt.events.is_empty() {
+            let mut event_hasher = Sha256::new();
+            for event in receipt.events {
+                event_hasher.update(&(event.contract.len() as u32).to_be_bytes());
+                event_hasher.update(event.contract);
+                event_hasher.update(&(event.topics.len() as u32).to_be_bytes());
+                for topic in event.topics {
+                    event_hasher.update(&(topic.len() as u32).to_be_bytes());
+                    event_hasher.update(topic);
+                }
+                event_hasher.update(&(event.data.len() as u32).to_be_bytes());
+                event_hasher.update(event.data);
+            }
+            kvs.push(bintree::Op::Insert(Some(b"events".to_vec()), receipt.txid.to_vec(), event_hasher.finalize().to_vec()));
+        }
+
+        if !receipt.results.is_empty() {
+            let mut results_hasher = Sha256::new();
+            for result in receipt.results {
+                results_hasher.update(&(result.len() as u32).to_be_bytes());
+                results_hasher.update(result);
+            }
+            kvs.push(bintree::Op::Insert(Some(b"results".to_vec()), receipt.txid.to_vec(), results_hasher.finalize().to_vec()));
+        }
     }
 
     let mut hubt = bintree::Hubt::new();
@@ -556,7 +642,7 @@ fn refund_exec_storage_deposit(applyenv: &mut ApplyEnv) {
     {
         let refund = applyenv.exec_left.max(0);
         if refund > 0 {
-            let key = &crate::bcat(&[b"account:", &applyenv.caller_env.account_origin, b":balance:AMA"]);
+            let key = &crate::bcat(&[b"account:", &applyenv.caller_env.account_payer, b":balance:AMA"]);
             consensus_kv::kv_increment(applyenv, key, refund);
         }
         // Increment validator / burn
@@ -569,7 +655,7 @@ fn refund_exec_storage_deposit(applyenv: &mut ApplyEnv) {
     {
         let refund = applyenv.storage_left.max(0);
         if refund > 0 {
-            let key = &crate::bcat(&[b"account:", &applyenv.caller_env.account_origin, b":balance:AMA"]);
+            let key = &crate::bcat(&[b"account:", &applyenv.caller_env.account_payer, b":balance:AMA"]);
             consensus_kv::kv_increment(applyenv, key, refund);
         }
         // Increment validator / burn
@@ -581,15 +667,17 @@ fn refund_exec_storage_deposit(applyenv: &mut ApplyEnv) {
     applyenv.muts_final_rev.append(&mut applyenv.muts_rev);
 }
 
-fn call_txs_pre_upfront_cost<'a>(env: &mut ApplyEnv, txus: &[crate::model::tx::TXU]) {
+//returns the exec and storage deposit reserved per tx, None fails the tx without executing
+fn call_txs_pre_upfront_cost<'a>(env: &mut ApplyEnv, txus: &[crate::model::tx::TXU]) -> Vec<Option<(i128, i128)>> {
     env.muts = Vec::new();
     env.muts_rev = Vec::new();
+    let mut reserved = Vec::with_capacity(txus.len());
     for txu in txus {
         let tx_hash = txu.hash.as_slice().try_into().unwrap_or_else(|_| panic!("tx_hash_len_wrong"));
         let tx_signer = txu.tx.signer.as_slice().try_into().unwrap_or_else(|_| panic!("tx_signer_len_wrong"));
         let tx_nonce = txu.tx.nonce;
 
-        set_apply_env_tx(env, &tx_hash, &tx_signer, tx_nonce);
+        set_apply_env_tx(env, &tx_hash, &tx_signer, tx_nonce, txu.tx.payer());
 
         // Update nonce
         consensus_kv::kv_put(env, &crate::bcat(&[b"account:", &tx_signer, b":attribute:nonce"]), &tx_nonce.to_string().into_bytes());
@@ -598,16 +686,116 @@ fn call_txs_pre_upfront_cost<'a>(env: &mut ApplyEnv, txus: &[crate::model::tx::T
         let tx_historical_cost = crate::consensus::bic::protocol::tx_historical_cost(txu);
         protocol::pay_cost(env, tx_historical_cost);
 
-        //lock 0.1 AMA during execution
-        consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &env.caller_env.account_origin, b":balance:AMA"]), -protocol::AMA_10_CENT);
-        //lock 1.0 storage AMA during execution
-        consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &env.caller_env.account_origin, b":balance:AMA"]), -protocol::AMA_1_DOLLAR);
+        //lock exec and storage limits during execution
+        let payer = env.caller_env.account_payer.clone();
+        let reservation = match protocol::tx_has_custom_limits(&txu.tx, env.testnet) {
+            false => Some((protocol::RESERVE_AMA_PER_TX_EXEC, protocol::RESERVE_AMA_PER_TX_STORAGE)),
+            true => {
+                let balance = consensus::bic::coin::balance(env, &payer, b"AMA");
+                protocol::tx_reservation(&txu.tx, balance)
+            }
+        };
+        if let Some((exec_limit, storage_limit)) = reservation {
+            consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &payer, b":balance:AMA"]), -exec_limit);
+            consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &payer, b":balance:AMA"]), -storage_limit);
+        }
+        reserved.push(reservation);
     }
     env.muts_final.append(&mut env.muts);
     env.muts_final_rev.append(&mut env.muts_rev);
+    reserved
+}
+
+//callbacks registered through Schedule.register, each runs like its own tx on the prepaid budget
+fn call_scheduled(env: &mut ApplyEnv) {
+    env.muts = Vec::new();
+    env.muts_rev = Vec::new();
+    //a corrupt queue skips the callbacks of this height instead of failing the entry
+    let due = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| consensus::bic::schedule::take_due(env))) {
+        Ok(due) => {
+            env.muts_final.append(&mut env.muts);
+            env.muts_final_rev.append(&mut env.muts_rev);
+            due
+        }
+        Err(_) => {
+            consensus_kv::revert(env);
+            Vec::new()
+        }
+    };
+
+    for scheduled in due {
+        env.caller_env.tx_hash = scheduled.id;
+        env.caller_env.tx_signer = scheduled.contract;
+        env.caller_env.tx_nonce = 0;
+        env.caller_env.account_origin = scheduled.contract.to_vec();
+        env.caller_env.account_payer = scheduled.contract.to_vec();
+        env.caller_env.account_caller = scheduled.contract.to_vec();
+        env.caller_env.account_current = scheduled.contract.to_vec();
+        env.caller_env.call_counter += 1;
+        env.muts = Vec::new();
+        env.muts_rev = Vec::new();
+        env.logs = Vec::new();
+        env.logs_size = 0;
+        env.events = Vec::new();
+        //exec and storage draw from the one prepaid budget
+        env.exec_track = true;
+        env.budget_shared = true;
+        env.exec_left = scheduled.budget;
+        env.exec_max = scheduled.budget;
+        env.storage_left = 0;
+        env.storage_max = 0;
+
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            call_wasmvm(env, scheduled.contract.to_vec(), scheduled.function.clone(), scheduled.args.clone(), None, None)
+        }));
+        env.exec_track = false;
+        env.budget_shared = false;
+        //like a tx, success or failure pays only for the exec actually used
+        let cost = (env.exec_max - env.exec_left).clamp(0, scheduled.budget);
+
+        let (success, result, events) = match res {
+            Ok(result) => {
+                env.muts_final.append(&mut env.muts);
+                env.muts_final_rev.append(&mut env.muts_rev);
+                (true, result, env.events.clone())
+            }
+            Err(payload) => {
+                consensus_kv::revert(env);
+                let result = match payload.downcast_ref::<&'static str>() {
+                    Some(&s) => s.as_bytes().to_vec(),
+                    None => b"unknown".to_vec(),
+                };
+                (false, result, Vec::new())
+            }
+        };
+
+        //refund the unused budget to the contract, split the rest between the validator and burn
+        env.muts = Vec::new();
+        env.muts_rev = Vec::new();
+        let refund = scheduled.budget - cost;
+        if refund > 0 {
+            consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &scheduled.contract, b":balance:AMA"]), refund);
+        }
+        consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &env.caller_env.entry_signer, b":balance:AMA"]), cost/2);
+        consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &consensus::bic::coin::BURN_ADDRESS, b":balance:AMA"]), cost/2);
+        env.muts_final.append(&mut env.muts);
+        env.muts_final_rev.append(&mut env.muts_rev);
+
+        env.receipts.push(TXReceipt {
+            txid: scheduled.id.to_vec(),
+            success: success,
+            result: result,
+            exec_used: cost.to_string().into_bytes(),
+            logs: env.logs.clone(),
+            events: events,
+            results: Vec::new(),
+        });
+    }
 }
 
 fn call_exit(env: &mut ApplyEnv) {
+    call_scheduled(env);
+
     //seed RNG for random validator selection
     let vr = env.caller_env.entry_vr.to_vec();
     let seed_hash = blake3::hash(&vr);
@@ -754,15 +942,50 @@ pub fn call_bic(env: &mut ApplyEnv, contract: Vec<u8>, function: Vec<u8>, args:
             (b"Coin", b"create_and_mint") => return consensus::bic::coin::call_create_and_mint(env, args),
             (b"Coin", b"mint") => return consensus::bic::coin::call_mint(env, args),
             (b"Coin", b"pause") => return consensus::bic::coin::call_pause(env, args),
+            (b"Coin", b"approve") => return consensus::bic::coin::call_approve(env, args),
+            (b"Coin", b"increase_allowance") => return consensus::bic::coin::call_increase_allowance(env, args),
+            (b"Coin", b"decrease_allowance") => return consensus::bic::coin::call_decrease_allowance(env, args),
+            (b"Coin", b"transfer_from") => return consensus::bic::coin::call_transfer_from(env, args),
+            (b"Coin", b"grant_role") => return consensus::bic::coin::call_grant_role(env, args),
+            (b"Coin", b"revoke_role") => return consensus::bic::coin::call_revoke_role(env, args),
+            (b"Coin", b"renounce_role") => return consensus::bic::coin::call_renounce_role(env, args),
+            (b"Coin", b"transfer_ownership") => return consensus::bic::coin::call_transfer_ownership(env, args),
+            (b"Coin", b"renounce_ownership") => return consensus::bic::coin::call_renounce_ownership(env, args),
+            (b"Coin", b"burn") => return consensus::bic::coin::call_burn(env, args),
+            (b"Coin", b"disable_mint") => return consensus::bic::coin::call_disable_mint(env, args),
+            (b"Coin", b"set_metadata") => return consensus::bic::coin::call_set_metadata(env, args),
+            (b"Coin", b"freeze") => return consensus::bic::coin::call_freeze(env, args),
+            (b"Coin", b"set_allowlist_mode") => return consensus::bic::coin::call_set_allowlist_mode(env, args),
+            (b"Coin", b"set_allowlisted") => return consensus::bic::coin::call_set_allowlisted(env, args),
             (b"Nft", b"transfer") => return consensus::bic::nft::call_transfer(env, args),
             (b"Nft", b"create_collection") => return consensus::bic::nft::call_create_collection(env, args),
             (b"Nft", b"mint") => return consensus::bic::nft::call_mint(env, args),
+            (b"Nft", b"set_collection_metadata") => return consensus::bic::nft::call_set_collection_metadata(env, args),
+            (b"Nft", b"set_token_metadata") => return consensus::bic::nft::call_set_token_metadata(env, args),
+            (b"Nft", b"approve") => return consensus::bic::nft::call_approve(env, args),
+            (b"Nft", b"set_approval_for_all") => return consensus::bic::nft::call_set_approval_for_all(env, args),
+            (b"Nft", b"transfer_from") => return consensus::bic::nft::call_transfer_from(env, args),
+            (b"Nft", b"burn") => return consensus::bic::nft::call_burn(env, args),
+            (b"Nft", b"burn_batch") => return consensus::bic::nft::call_burn_batch(env, args),
+            (b"Nft", b"mint_batch") => return consensus::bic::nft::call_mint_batch(env, args),
+            (b"Nft", b"transfer_batch") => return consensus::bic::nft::call_transfer_batch(env, args),
+            (b"Nft", b"set_royalty") => return consensus::bic::nft::call_set_royalty(env, args),
             (b"Lockup", b"lock") => return consensus::bic::lockup::call_lock(env, args),
             (b"Lockup", b"unlock") => return consensus::bic::lockup::call_unlock(env, args),
+            (b"Lockup", b"vest") => return consensus::bic::lockup::call_vest(env, args),
+            (b"Lockup", b"claim") => return consensus::bic::lockup::call_claim(env, args),
             (b"Contract", b"deploy") => {
                 consensus_kv::exec_budget_decr(env, protocol::COST_PER_DEPLOY);
                 return consensus::bic::contract::call_deploy(env, args);
             }
+            (b"Contract", b"upgrade") => {
+                consensus_kv::exec_budget_decr(env, protocol::COST_PER_DEPLOY);
+                return consensus::bic::contract::call_upgrade(env, args);
+            }
+            (b"Contract", b"freeze") => return consensus::bic::contract::call_freeze(env, args),
+            (b"Contract", b"set_upgrade_authority") => return consensus::bic::contract::call_set_upgrade_authority(env, args),
+            (b"Schedule", b"register") => return consensus::bic::schedule::call_register(env, args),
+            (b"Schedule", b"cancel") => return consensus::bic::schedule::call_cancel(env, args),
             (b"LockupPrime", b"lock") => return consensus::bic::lockup_prime::call_lock(env, args),
             (b"LockupPrime", b"unlock") => return consensus::bic::lockup_prime::call_unlock(env, args),
             (b"LockupPrime", b"daily_checkin") => return consensus::bic::lockup_prime::call_daily_checkin(env, args),
@@ -803,6 +1026,23 @@ pub fn call_bic(env: &mut ApplyEnv, contract: Vec<u8>, function: Vec<u8>, args:
     }
 }
 
+// Readonly BIC functions, return a vecpak encoded result
+pub fn call_bic_view(env: &mut ApplyEnv, contract: &[u8], function: &[u8], args: &[Vec<u8>]) -> Option<Vec<u8>> {
+    match (contract, function) {
+        (b"Contract", b"view_metadata") => Some(consensus::bic::contract::view_metadata(env, args)),
+        (b"Coin", b"view_allowance") => Some(consensus::bic::coin::view_allowance(env, args)),
+        (b"Coin", b"view_coin") => Some(consensus::bic::coin::view_coin(env, args)),
+        (b"Nft", b"view_collection") => Some(consensus::bic::nft::view_collection(env, args)),
+        (b"Nft", b"view_token") => Some(consensus::bic::nft::view_token(env, args)),
+        (b"Nft", b"view_royalty") => Some(consensus::bic::nft::view_royalty(env, args)),
+        (b"Lockup", b"view_vaults") => Some(consensus::bic::lockup::view_vaults(env, args)),
+        (b"Lockup", b"view_vault_totals") => Some(consensus::bic::lockup::view_vault_totals(env, args)),
+        (b"LockupPrime", b"view_vaults") => Some(consensus::bic::lockup_prime::view_vaults(env, args)),
+        (b"LockupPrime", b"view_vault_totals") => Some(consensus::bic::lockup_prime::view_vault_totals(env, args)),
+        _ => None
+    }
+}
+
 pub fn call_wasmvm(env: &mut ApplyEnv, contract: Vec<u8>, function: Vec<u8>, args: Vec<Vec<u8>>, attached_symbol: Option<Vec<u8>>, attached_amount: Option<Vec<u8>>) -> Vec<u8> {
     let function = String::from_utf8(function).unwrap_or_else(|_| panic_any("invalid_function"));
 
@@ -831,6 +1071,8 @@ pub fn call_wasmvm(env: &mut ApplyEnv, contract: Vec<u8>, function: Vec<u8>, arg
             let amount = std::str::from_utf8(&attached_amount).ok().and_then(|s| s.parse::<i128>().ok()).unwrap_or_else(|| panic_any("invalid_attached_amount"));
             if amount <= 0 { panic_any("invalid_attached_amount") }
             if amount > consensus::bic::coin::balance(env, &env.caller_env.account_caller.clone(), &attached_symbol) { panic_any("attached_amount_insufficient_funds") }
+            consensus::bic::coin::check_account(env, &attached_symbol, &env.caller_env.account_caller.clone());
+            consensus::bic::coin::check_account(env, &attached_symbol, &contract);
 
             consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &contract, b":balance:", &attached_symbol]), amount);
             consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", &env.caller_env.account_caller, b":balance:", &attached_symbol]), -amount);
@@ -848,3 +1090,176 @@ pub fn call_wasmvm(env: &mut ApplyEnv, contract: Vec<u8>, function: Vec<u8>, arg
     let error = consensus::bic::wasm::call_contract(env, bytecode.as_deref().unwrap_or_else(|| panic_any("invalid_bytecode")), function, args);
     error
 }
+
+//fresh rocksdb per call so bic logic can be exercised without an entry
+#[cfg(test)]
+pub fn with_test_env<R>(testnet: bool, f: impl FnOnce(&mut ApplyEnv) -> R) -> R {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static NEXT_DB: AtomicU64 = AtomicU64::new(0);
+
+    let path = std::env::temp_dir().join(format!("rdb_test_{}_{}", std::process::id(), NEXT_DB.fetch_add(1, Ordering::SeqCst)));
+    let mut opts = crate::Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db: TransactionDB<MultiThreaded> = TransactionDB::open_cf(&opts, &crate::TransactionDBOptions::default(), &path,
+        ["contractstate", "contractstate_tree"]).unwrap();
+
+    let result = {
+        let cf = db.cf_handle("contractstate").unwrap();
+        let cf_tree = db.cf_handle("contractstate_tree").unwrap();
+        let mut env = make_apply_env(&db, db.transaction(), cf.clone(), b"contractstate".to_vec(), cf, cf_tree,
+            &[7u8; 48], &[0u8; 32], 1, 0, 1, 0, &[0u8; 96], &[0u8; 32], &[0u8; 32],
+            testnet, Vec::new());
+        f(&mut env)
+    };
+    drop(db);
+    let _ = std::fs::remove_dir_all(&path);
+    result
+}
+
+//shared by the bic tests that run against with_test_env
+#[cfg(test)]
+pub mod test_util {
+    use super::ApplyEnv;
+
+    pub fn pk(seed: u8) -> Vec<u8> {
+        crate::consensus::bls12_381::get_public_key(&[seed; 64]).unwrap().to_vec()
+    }
+
+    pub fn as_caller(env: &mut ApplyEnv, account: &[u8]) {
+        env.caller_env.account_caller = account.to_vec();
+    }
+
+    //the panic_any reason of a call expected to fail
+    pub fn err(f: impl FnOnce()) -> &'static str {
+        let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_err();
+        *payload.downcast::<&'static str>().unwrap()
+    }
+
+    pub fn args(list: &[&[u8]]) -> Vec<Vec<u8>> {
+        list.iter().map(|a| a.to_vec()).collect()
+    }
+
+    //(module (import "env" "memory" (memory 1)) (func (export "tick")) (func (export "boom") unreachable))
+    pub const TICK_BOOM_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+        0x02, 0x0f, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72, 0x79, 0x02, 0x00, 0x01,
+        0x03, 0x03, 0x02, 0x00, 0x00,
+        0x07, 0x0f, 0x02, 0x04, 0x74, 0x69, 0x63, 0x6b, 0x00, 0x00, 0x04, 0x62, 0x6f, 0x6f, 0x6d, 0x00, 0x01,
+        0x0a, 0x08, 0x02, 0x02, 0x00, 0x0b, 0x03, 0x00, 0x00, 0x0b,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tx::{Action, TX, TXU};
+
+    fn txu(signer: [u8; 48], nonce: u64, exec_limit: Option<i128>, storage_limit: Option<i128>) -> TXU {
+        let action = Action {
+            op: b"call".to_vec(), contract: b"Coin".to_vec(), function: b"transfer".to_vec(),
+            args: Vec::new(), attached_symbol: None, attached_amount: None,
+        };
+        TXU {
+            hash: vec![nonce as u8; 32],
+            signature: vec![0u8; 96],
+            tx: TX { signer: signer.to_vec(), nonce, actions: vec![action], sponsor: None, exec_limit, storage_limit },
+            sponsor_signature: None,
+        }
+    }
+
+    fn fund(env: &mut ApplyEnv, account: &[u8], amount: i128) {
+        consensus_kv::kv_increment(env, &crate::bcat(&[b"account:", account, b":balance:AMA"]), amount);
+    }
+
+    #[test]
+    fn reservation_reserve_fail_and_refund() {
+        with_test_env(true, |env| {
+            let legacy = txu([1u8; 48], 1, None, None);
+            let custom = txu([2u8; 48], 1, Some(protocol::TX_MIN_EXEC_LIMIT), Some(0));
+            let broke = txu([3u8; 48], 1, Some(protocol::TX_MAX_EXEC_LIMIT), Some(protocol::TX_MAX_STORAGE_LIMIT));
+            for t in [&legacy, &custom, &broke] {
+                fund(env, &t.tx.signer, protocol::AMA_1_DOLLAR * 2);
+            }
+            let costs: Vec<i128> = [&legacy, &custom, &broke].iter().map(|t| protocol::tx_historical_cost(t)).collect();
+
+            let reserved = call_txs_pre_upfront_cost(env, &[legacy.clone(), custom.clone(), broke.clone()]);
+            assert_eq!(reserved, vec![
+                Some((protocol::RESERVE_AMA_PER_TX_EXEC, protocol::RESERVE_AMA_PER_TX_STORAGE)),
+                Some((protocol::TX_MIN_EXEC_LIMIT, 0)),
+                None,
+            ]);
+
+            let balance = |env: &mut ApplyEnv, t: &TXU| consensus::bic::coin::balance(env, &t.tx.signer, b"AMA");
+            let start = protocol::AMA_1_DOLLAR * 2;
+            assert_eq!(balance(env, &legacy), start - costs[0] - protocol::RESERVE_AMA_PER_TX_EXEC - protocol::RESERVE_AMA_PER_TX_STORAGE);
+            assert_eq!(balance(env, &custom), start - costs[1] - protocol::TX_MIN_EXEC_LIMIT);
+            //an unreserved tx still pays for its bytes but nothing is locked
+            assert_eq!(balance(env, &broke), start - costs[2]);
+
+            //a third of the exec deposit used, storage untouched
+            set_apply_env_tx(env, &[1u8; 32], &[1u8; 48], 1, &legacy.tx.signer);
+            env.exec_max = protocol::RESERVE_AMA_PER_TX_EXEC;
+            env.exec_left = protocol::RESERVE_AMA_PER_TX_EXEC - 3_000;
+            env.storage_max = protocol::RESERVE_AMA_PER_TX_STORAGE;
+            env.storage_left = protocol::RESERVE_AMA_PER_TX_STORAGE;
+            let validator_before = consensus::bic::coin::balance(env, &[7u8; 48], b"AMA");
+            refund_exec_storage_deposit(env);
+            assert_eq!(balance(env, &legacy), start - costs[0] - 3_000);
+            assert_eq!(consensus::bic::coin::balance(env, &[7u8; 48], b"AMA"), validator_before + 1_500);
+        });
+    }
+
+    #[test]
+    fn reservation_legacy_on_mainnet() {
+        with_test_env(false, |env| {
+            //limits are ignored off testnet, the default reserve is debited even without the funds
+            let t = txu([4u8; 48], 1, Some(protocol::TX_MIN_EXEC_LIMIT), Some(0));
+            let reserved = call_txs_pre_upfront_cost(env, &[t]);
+            assert_eq!(reserved, vec![Some((protocol::RESERVE_AMA_PER_TX_EXEC, protocol::RESERVE_AMA_PER_TX_STORAGE))]);
+        });
+    }
+
+    fn schedule(env: &mut ApplyEnv, contract: &[u8], bytecode: &[u8], function: &[u8]) {
+        consensus_kv::kv_put(env, &crate::bcat(&[b"account:", contract, b":attribute:bytecode"]), bytecode);
+        test_util::as_caller(env, contract);
+        let budget = protocol::SCHEDULE_MAX_BUDGET.to_string().into_bytes();
+        consensus::bic::schedule::call_register(env, test_util::args(&[b"2", function, &budget]));
+    }
+
+    #[test]
+    fn scheduled_callbacks_run_and_pay_actual_usage() {
+        with_test_env(true, |env| {
+            let (c, broken) = (test_util::pk(1), test_util::pk(2));
+            fund(env, &c, protocol::SCHEDULE_MAX_BUDGET * 2);
+            fund(env, &broken, protocol::SCHEDULE_MAX_BUDGET);
+            schedule(env, &c, test_util::TICK_BOOM_WASM, b"tick");
+            schedule(env, &c, test_util::TICK_BOOM_WASM, b"boom");
+            schedule(env, &broken, b"\0asm", b"tick");
+            assert_eq!(consensus::bic::coin::balance(env, &c, b"AMA"), 0);
+
+            env.caller_env.entry_height = 2;
+            let validator_before = consensus::bic::coin::balance(env, &[7u8; 48], b"AMA");
+            call_scheduled(env);
+
+            assert_eq!(env.receipts.len(), 3);
+            let cost = |r: &TXReceipt| -> i128 { std::str::from_utf8(&r.exec_used).unwrap().parse().unwrap() };
+            let (ok, failed): (Vec<&TXReceipt>, Vec<&TXReceipt>) = env.receipts.iter().partition(|r| r.success);
+            assert_eq!(ok.len(), 1);
+            let invalid: Vec<&&TXReceipt> = failed.iter().filter(|r| r.result == b"exec_invalid_module").collect();
+            assert_eq!(invalid.len(), 1);
+
+            //failures pay what they used, not the whole budget
+            let costs: Vec<i128> = env.receipts.iter().map(cost).collect();
+            assert!(costs.iter().all(|&cost| cost > 0 && cost < protocol::SCHEDULE_MAX_BUDGET));
+            let broken_cost = cost(*invalid[0]);
+            let c_cost: i128 = costs.iter().sum::<i128>() - broken_cost;
+            assert_eq!(consensus::bic::coin::balance(env, &c, b"AMA"), protocol::SCHEDULE_MAX_BUDGET * 2 - c_cost);
+            assert_eq!(consensus::bic::coin::balance(env, &broken, b"AMA"), protocol::SCHEDULE_MAX_BUDGET - broken_cost);
+            let validator_cut: i128 = costs.iter().map(|cost| cost / 2).sum();
+            assert_eq!(consensus::bic::coin::balance(env, &[7u8; 48], b"AMA"), validator_before + validator_cut);
+            assert!(consensus::bic::schedule::take_due(env).is_empty());
+        });
+    }
+}
@@ -29,6 +29,9 @@ pub fn storage_budget_decr(env: &mut ApplyEnv, amount: i128) {
     if amount < 0 {
          panic_any("exec_storage_invalid_amount_negative");
     }
+    if env.budget_shared {
+        return exec_budget_decr(env, amount)
+    }
 
     if env.exec_track {
         match env.storage_left.checked_sub(amount) {
@@ -44,6 +47,14 @@ pub fn storage_budget_decr(env: &mut ApplyEnv, amount: i128) {
     }
 }
 
+//only tracked while profiling a view
+fn profile_kv_bytes(env: &mut ApplyEnv, read: usize, written: usize) {
+    if let Some(profile) = env.profile.as_mut() {
+        profile.kv_read_bytes += read as u64;
+        profile.kv_write_bytes += written as u64;
+    }
+}
+
 pub fn exec_kv_size(key: &[u8], value: Option<&[u8]>) {
     if key.len() > protocol::MAX_DB_KEY_SIZE {
          panic_any("exec_too_large_key_size");
@@ -62,6 +73,7 @@ pub fn kv_put(env: &mut ApplyEnv, key: &[u8], value: &[u8]) {
 
     exec_kv_size(key, Some(value));
     exec_budget_decr(env, protocol::COST_PER_DB_WRITE_BASE + protocol::cost_db_write_byte(env) * (key.len() + value.len()) as i128);
+    profile_kv_bytes(env, 0, key.len() + value.len());
 
     let old_value = env.txn.get_cf(&env.cf, key).unwrap();
     match old_value {
@@ -91,6 +103,7 @@ pub fn kv_increment(env: &mut ApplyEnv, key: &[u8], value: i128) -> i128 {
 
     let value_str = value.to_string().into_bytes();
     exec_budget_decr(env, protocol::COST_PER_DB_WRITE_BASE + protocol::cost_db_write_byte(env) * (key.len() + value_str.len()) as i128);
+    profile_kv_bytes(env, 0, key.len() + value_str.len());
 
     match env.txn.get_cf(&env.cf, key).unwrap() {
         None => {
@@ -175,7 +188,9 @@ pub fn kv_exists(env: &mut ApplyEnv, key: &[u8]) -> bool {
 pub fn kv_get(env: &mut ApplyEnv, key: &[u8]) -> Option<Vec<u8>> {
     exec_budget_decr(env, protocol::COST_PER_DB_READ_BASE + protocol::cost_db_read_byte(env) * (key.len()) as i128);
 
-    env.txn.get_cf(&env.cf, key).unwrap()
+    let value = env.txn.get_cf(&env.cf, key).unwrap();
+    profile_kv_bytes(env, key.len() + value.as_ref().map_or(0, |v| v.len()), 0);
+    value
 }
 
 pub fn kv_get_next(env: &mut ApplyEnv, prefix: &[u8], key: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
@@ -247,6 +262,58 @@ pub fn kv_get_prev_or_first(env: &mut ApplyEnv, prefix: &[u8], key: &[u8]) -> Op
     }
 }
 
+// Walks [prefix+start, prefix+end) with a single iterator, keys are returned without the prefix
+// An empty end means until the end of the prefix, reverse walks from end down to start
+pub fn kv_scan(env: &mut ApplyEnv, prefix: &[u8], start: &[u8], end: &[u8], limit: usize, reverse: bool) -> Vec<(Vec<u8>, Vec<u8>)> {
+    exec_budget_decr(env, protocol::COST_PER_DB_READ_BASE + protocol::cost_db_read_byte(env) * (prefix.len() + start.len() + end.len()) as i128);
+
+    let limit = limit.min(protocol::KV_SCAN_MAX_ITEMS);
+    let read_byte = protocol::cost_db_read_byte(env);
+    let mut results = Vec::new();
+    let mut packed_size = 4;
+    let mut cost: i128 = 0;
+
+    let mut it = env.txn.raw_iterator_cf(&env.cf);
+    if reverse {
+        if end.is_empty() {
+            it.seek_for_prev(&[prefix, &vec![0xFF; protocol::MAX_DB_KEY_SIZE]].concat());
+        } else {
+            let seek = [prefix, end].concat();
+            it.seek_for_prev(&seek);
+            if it.valid() && it.key() == Some(seek.as_slice()) {
+                it.prev();
+            }
+        }
+    } else {
+        it.seek(&[prefix, start].concat());
+    }
+
+    while results.len() < limit && it.valid() {
+        let (k, v) = match it.item() {
+            Some((k, v)) if k.starts_with(prefix) => (k, v),
+            _ => break
+        };
+        let key_wo_prefix = &k[prefix.len()..];
+        if reverse && key_wo_prefix < start { break }
+        if !reverse && !end.is_empty() && key_wo_prefix >= end { break }
+
+        //the first pair always goes out so a large value never reads as the end of the range
+        let item_size = 8 + key_wo_prefix.len() + v.len();
+        if !results.is_empty() && packed_size + item_size > protocol::KV_SCAN_MAX_BYTES { break }
+        packed_size += item_size;
+
+        cost += protocol::COST_PER_DB_SCAN_ITEM + read_byte * (k.len() + v.len()) as i128;
+        results.push((key_wo_prefix.to_vec(), v.to_vec()));
+
+        if reverse { it.prev(); } else { it.next(); }
+    }
+    drop(it);
+
+    exec_budget_decr(env, cost);
+    profile_kv_bytes(env, packed_size, 0);
+    results
+}
+
 pub fn contractstate_namespace(key: &[u8]) -> Option<Vec<u8>> {
     if key.starts_with(b"account:") {
         Some(key[0..56].to_vec())
@@ -0,0 +1,162 @@
+pub use rust_rocksdb::{TransactionDB, MultiThreaded, ReadOptions};
+
+use rustler::{Binary, Env, NewBinary, NifResult, Term};
+use vecpak::Term as VTerm;
+
+rustler::atoms! {
+    txid,
+    events,
+    contract,
+    topics,
+    data,
+}
+//tagged so the all-events bucket of a contract never hashes like one of its topics
+const TAG_ALL: &[u8] = b"all:";
+const TAG_TOPIC: &[u8] = b"topic:";
+
+fn bucket_parts<'b>(contract: &'b [u8], topic: Option<&'b [u8]>) -> Vec<&'b [u8]> {
+    match topic {
+        None => vec![TAG_ALL, contract],
+        Some(topic) => vec![TAG_TOPIC, contract, topic],
+    }
+}
+
+//hash16 + height BE8 + tx_index BE4 + event_index BE2
+const KEY_SIZE: usize = 30;
+
+pub fn build_event_filters<'a>(env: Env<'a>, height: u64, receipts: Vec<Term<'a>>) -> NifResult<Vec<(Binary<'a>, Binary<'a>)>> {
+    let mut all_filters = Vec::new();
+
+    for (tx_index, receipt) in receipts.into_iter().enumerate() {
+        let events_list: Vec<Term> = match receipt.map_get(events()) {
+            Ok(list) => list.decode()?,
+            Err(_) => continue,
+        };
+        if events_list.is_empty() { continue }
+
+        let txid_bin: Binary = receipt.map_get(txid())?.decode()?;
+
+        for (event_index, event) in events_list.into_iter().enumerate() {
+            let contract_bin: Binary = event.map_get(contract())?.decode()?;
+            let contract = contract_bin.as_slice();
+            let topics_bins: Vec<Binary> = event.map_get(topics())?.decode()?;
+            let data_bin: Binary = event.map_get(data())?.decode()?;
+
+            let value = vecpak::encode(VTerm::PropList(vec![
+                (VTerm::Binary(b"txid".to_vec()), VTerm::Binary(txid_bin.as_slice().to_vec())),
+                (VTerm::Binary(b"height".to_vec()), VTerm::VarInt(height as i128)),
+                (VTerm::Binary(b"tx_index".to_vec()), VTerm::VarInt(tx_index as i128)),
+                (VTerm::Binary(b"event_index".to_vec()), VTerm::VarInt(event_index as i128)),
+                (VTerm::Binary(b"contract".to_vec()), VTerm::Binary(contract.to_vec())),
+                (VTerm::Binary(b"topics".to_vec()), VTerm::List(topics_bins.iter().map(|t| VTerm::Binary(t.as_slice().to_vec())).collect())),
+                (VTerm::Binary(b"data".to_vec()), VTerm::Binary(data_bin.as_slice().to_vec())),
+            ]));
+            let mut value_bin = NewBinary::new(env, value.len());
+            value_bin.as_mut_slice().copy_from_slice(&value);
+            let value_bin: Binary = value_bin.into();
+
+            let mut push_key = |topic: Option<&[u8]>| {
+                let raw_hash = crate::tx_filter::create_filter_key(&bucket_parts(contract, topic));
+
+                let mut bin = NewBinary::new(env, KEY_SIZE);
+                let s = bin.as_mut_slice();
+
+                s[0..16].copy_from_slice(&raw_hash);
+                s[16..24].copy_from_slice(&height.to_be_bytes());
+                s[24..28].copy_from_slice(&(tx_index as u32).to_be_bytes());
+                s[28..30].copy_from_slice(&(event_index as u16).to_be_bytes());
+
+                all_filters.push((bin.into(), value_bin));
+            };
+
+            push_key(None);
+            let mut seen: Vec<&[u8]> = Vec::with_capacity(topics_bins.len());
+            for topic in topics_bins.iter() {
+                let topic = topic.as_slice();
+                if seen.contains(&topic) { continue }
+                seen.push(topic);
+                push_key(Some(topic));
+            }
+        }
+    }
+
+    Ok(all_filters)
+}
+
+pub fn query_event_filter<'a, 'db>(env: Env<'a>, db: &'db TransactionDB<MultiThreaded>, contract: &[u8], topic: Option<&[u8]>,
+    limit: usize, sort: bool, cursor: Option<&[u8]>) -> NifResult<(Option<Binary<'a>>, Vec<Binary<'a>>)>
+{
+    let cf_eventfilter = &db.cf_handle("event_filter").unwrap();
+
+    let snapshot = &db.snapshot();
+
+    let mut opts = ReadOptions::default();
+    opts.set_snapshot(&snapshot);
+    opts.set_prefix_same_as_start(true);
+
+    let mut iter = db.raw_iterator_cf_opt(cf_eventfilter, opts);
+
+    let key = crate::tx_filter::create_filter_key(&bucket_parts(contract, topic));
+    let prefix = &key[0..16];
+
+    let is_desc = sort == true;
+
+    let start_key = if let Some(c) = cursor {
+        c.to_vec()
+    } else {
+        let mut k = Vec::with_capacity(KEY_SIZE);
+        k.extend_from_slice(prefix);
+        if is_desc {
+            k.extend_from_slice(&[0xFF; KEY_SIZE - 16]); // End of bucket
+        } else {
+            k.extend_from_slice(&[0x00; KEY_SIZE - 16]); // Start of bucket
+        }
+        k
+    };
+
+    if is_desc {
+        iter.seek_for_prev(&start_key);
+    } else {
+        iter.seek(&start_key);
+    }
+
+    // Skip the cursor itself if present (Pagination "After")
+    if cursor.is_some() && iter.valid() {
+        if let Some(k) = iter.key() {
+            if k == &start_key {
+                if is_desc { iter.prev(); } else { iter.next(); }
+            }
+        }
+    }
+
+    let mut results = Vec::new();
+    let mut last_cursor_bytes: Option<Vec<u8>> = None;
+    while iter.valid() {
+        match iter.key() {
+            Some(k) if k.len() >= 16 && &k[0..16] == prefix => {
+                last_cursor_bytes = Some(k.to_vec());
+            },
+            _ => break,
+        }
+
+        let event_data = iter.value().unwrap();
+        let mut bin = NewBinary::new(env, event_data.len());
+        bin.as_mut_slice().copy_from_slice(event_data);
+        results.push(bin.into());
+        if results.len() >= limit {
+            break
+        }
+
+        if is_desc { iter.prev(); } else { iter.next(); }
+    }
+
+    let cursor_bin = match last_cursor_bytes {
+        Some(v) => {
+            let mut bin = NewBinary::new(env, v.len());
+            bin.as_mut_slice().copy_from_slice(&v);
+            Some(bin.into())
+        },
+        None => None
+    };
+    Ok((cursor_bin, results))
+}
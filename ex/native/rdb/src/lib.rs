@@ -2,6 +2,7 @@ pub mod consensus;
 pub mod atoms;
 pub mod model;
 pub mod tx_filter;
+pub mod event_filter;
 
 use rustler::types::{Binary, OwnedBinary};
 use rustler::{
@@ -253,6 +254,10 @@ fn open_transaction_db<'a>(env: Env<'a>, path: String, cf_names: Vec<String>) ->
                 opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(16));
                 opts.set_memtable_prefix_bloom_ratio(0.1);
             }
+            if name == "event_filter" {
+                opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(16));
+                opts.set_memtable_prefix_bloom_ratio(0.1);
+            }
 
             ColumnFamilyDescriptor::new(name.as_str(), opts)
         })
@@ -737,6 +742,21 @@ fn apply_entry<'a>(env: Env<'a>, db: ResourceArc<DbResource>, entry_vecpak: Bina
             to_binary2(env, log)
         }).collect();
         map = map.map_put(atoms::logs(), logs_list).ok().unwrap();
+        if !r.events.is_empty() {
+            let events_list: Vec<Term> = r.events.iter().map(|event| {
+                let mut event_map = Term::map_new(env);
+                event_map = event_map.map_put(atoms::contract(), to_binary2(env, &event.contract)).ok().unwrap();
+                let topics_list: Vec<Binary> = event.topics.iter().map(|topic| to_binary2(env, topic)).collect();
+                event_map = event_map.map_put(atoms::topics(), topics_list).ok().unwrap();
+                event_map = event_map.map_put(atoms::data(), to_binary2(env, &event.data)).ok().unwrap();
+                event_map
+            }).collect();
+            map = map.map_put(atoms::events(), events_list).ok().unwrap();
+        }
+        if !r.results.is_empty() {
+            let results_list: Vec<Binary> = r.results.iter().map(|result| to_binary2(env, result)).collect();
+            map = map.map_put(atoms::results(), results_list).ok().unwrap();
+        }
         receipts_list.push(map);
     }
 
@@ -744,20 +764,29 @@ fn apply_entry<'a>(env: Env<'a>, db: ResourceArc<DbResource>, entry_vecpak: Bina
         Binary::from_owned(ob1, env).encode(env), Binary::from_owned(ob2, env).encode(env)).encode(env))
 }
 
+//typed abi results come back as native terms, everything else as the raw bytes
+fn view_result_term<'a>(env: Env<'a>, result: &[u8], typed: bool) -> Result<Term<'a>, Error> {
+    if typed {
+        return vecpak_ex::decode_term_from_slice(env, result);
+    }
+    let mut ob_result = OwnedBinary::new(result.len()).ok_or_else(|| Error::Term(Box::new("alloc failed"))).unwrap();
+    ob_result.as_mut_slice().copy_from_slice(result);
+    Ok(Binary::from_owned(ob_result, env).encode(env))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 fn contract_view<'a>(env: Env<'a>, db: ResourceArc<DbResource>, entry_vecpak: Binary, view_pk: Binary,
     contract: Binary, function: Binary, fargs: Vec<Binary>, testnet: bool) -> Result<Term<'a>, Error>
 {
     let entry = crate::model::entry::from_bytes(entry_vecpak.as_slice()).map_err(|_| Error::BadArg)?;
 
-    let (success, result, logs) = consensus::consensus_apply::contract_view(
+    let (success, result, logs, typed) = consensus::consensus_apply::contract_view(
         &db.db, entry, view_pk.as_slice().to_vec(),
         contract.as_slice().to_vec(), function.as_slice().to_vec(), fargs.iter().map(|bin| bin.as_slice().to_vec()).collect(),
         testnet
     );
 
-    let mut ob_result = OwnedBinary::new(result.len()).ok_or_else(|| Error::Term(Box::new("alloc failed"))).unwrap();
-    ob_result.as_mut_slice().copy_from_slice(&result);
+    let result_term = view_result_term(env, &result, typed)?;
 
     let mut logs_list = Vec::new();
     for l in logs {
@@ -766,7 +795,46 @@ fn contract_view<'a>(env: Env<'a>, db: ResourceArc<DbResource>, entry_vecpak: Bi
         logs_list.push(Binary::from_owned(ob_log, env))
     };
 
-    Ok((success, Binary::from_owned(ob_result, env), logs_list).encode(env))
+    Ok((success, result_term, logs_list).encode(env))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn contract_view_profile<'a>(env: Env<'a>, db: ResourceArc<DbResource>, entry_vecpak: Binary, view_pk: Binary,
+    contract: Binary, function: Binary, fargs: Vec<Binary>, testnet: bool) -> Result<Term<'a>, Error>
+{
+    let entry = crate::model::entry::from_bytes(entry_vecpak.as_slice()).map_err(|_| Error::BadArg)?;
+
+    let (success, result, logs, typed, report) = consensus::consensus_apply::contract_view_profiled(
+        &db.db, entry, view_pk.as_slice().to_vec(),
+        contract.as_slice().to_vec(), function.as_slice().to_vec(), fargs.iter().map(|bin| bin.as_slice().to_vec()).collect(),
+        testnet, true
+    );
+
+    let result_term = view_result_term(env, &result, typed)?;
+
+    let mut logs_list = Vec::new();
+    for l in logs {
+        let mut ob_log = OwnedBinary::new(l.len()).ok_or_else(|| Error::Term(Box::new("alloc failed"))).unwrap();
+        ob_log.as_mut_slice().copy_from_slice(&l);
+        logs_list.push(Binary::from_owned(ob_log, env))
+    };
+
+    let report = report.unwrap_or_default();
+    let mut ob_report = OwnedBinary::new(report.len()).ok_or_else(|| Error::Term(Box::new("alloc failed"))).unwrap();
+    ob_report.as_mut_slice().copy_from_slice(&report);
+
+    Ok((success, result_term, logs_list, Binary::from_owned(ob_report, env)).encode(env))
+}
+
+//abi registry record written at deploy, nil for accounts without one
+#[rustler::nif]
+fn contract_abi<'a>(env: Env<'a>, db: ResourceArc<DbResource>, contract: Binary) -> NifResult<Term<'a>> {
+    let cf = db.db.cf_handle("contractstate").ok_or(Error::BadArg)?;
+    match db.db.get_cf(&cf, bcat(&[b"account:", contract.as_slice(), b":attribute:abi"])) {
+        Ok(Some(value)) => vecpak_ex::decode_term_from_slice(env, &value),
+        Ok(None) => Ok(atoms::nil().encode(env)),
+        Err(e) => Err(to_nif_rdb_err(e)),
+    }
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -1036,6 +1104,10 @@ fn protocol_constants<'a>(env: Env<'a>) -> Term<'a> {
 
     map = map.map_put(atoms::reserve_ama_per_tx_exec(), protocol::RESERVE_AMA_PER_TX_EXEC).ok().unwrap();
     map = map.map_put(atoms::reserve_ama_per_tx_storage(), protocol::RESERVE_AMA_PER_TX_STORAGE).ok().unwrap();
+    map = map.map_put(atoms::tx_max_actions(), protocol::TX_MAX_ACTIONS).ok().unwrap();
+    map = map.map_put(atoms::tx_min_exec_limit(), protocol::TX_MIN_EXEC_LIMIT).ok().unwrap();
+    map = map.map_put(atoms::tx_max_exec_limit(), protocol::TX_MAX_EXEC_LIMIT).ok().unwrap();
+    map = map.map_put(atoms::tx_max_storage_limit(), protocol::TX_MAX_STORAGE_LIMIT).ok().unwrap();
 
     map = map.map_put(atoms::cost_per_byte_historical(), protocol::COST_PER_BYTE_HISTORICAL).ok().unwrap();
     map = map.map_put(atoms::cost_per_byte_state(), protocol::COST_PER_BYTE_STATE).ok().unwrap();
@@ -1079,4 +1151,15 @@ fn query_tx_hashfilter<'a>(env: Env<'a>, db: ResourceArc<DbResource>, signer: Bi
     tx_filter::query_tx_hashfilter(env, &db.db, &signer, &arg0, &contract, &function, limit as usize, sort, cursor.map(|b| b.as_slice()))
 }
 
+#[rustler::nif]
+fn build_event_filters<'a>(env: Env<'a>, height: u64, receipts: Vec<Term<'a>>) -> NifResult<Vec<(Binary<'a>, Binary<'a>)>> {
+    event_filter::build_event_filters(env, height, receipts)
+}
+
+#[rustler::nif]
+fn query_event_filter<'a>(env: Env<'a>, db: ResourceArc<DbResource>, contract: Binary<'a>, topic: Option<Binary<'a>>,
+    limit: u32, sort: bool, cursor: Option<Binary<'a>>) -> NifResult<(Option<Binary<'a>>, Vec<Binary<'a>>)> {
+    event_filter::query_event_filter(env, &db.db, &contract, topic.as_ref().map(|b| b.as_slice()), limit as usize, sort, cursor.map(|b| b.as_slice()))
+}
+
 rustler::init!("Elixir.RDB", load = on_load);
@@ -12,18 +12,41 @@ pub struct Action {
     pub attached_amount: Option<Vec<u8>>,
 }
 
+//a single action encodes as `action` exactly like before, several as the `actions` list
+//and execute atomically in order
+//the optional sponsor pays the historical cost and deposits instead of the signer
+//exec_limit and storage_limit are the deposits reserved upfront, omitted means the protocol reserve
 #[derive(Debug, Clone)]
 pub struct TX {
     pub signer: Vec<u8>,
     pub nonce: u64,
-    pub action: Action,
+    pub actions: Vec<Action>,
+    pub sponsor: Option<Vec<u8>>,
+    pub exec_limit: Option<i128>,
+    pub storage_limit: Option<i128>,
 }
 
+impl TX {
+    pub fn payer(&self) -> &[u8] {
+        self.sponsor.as_deref().unwrap_or(&self.signer)
+    }
+
+    pub fn exec_limit(&self) -> i128 {
+        self.exec_limit.unwrap_or(crate::consensus::bic::protocol::RESERVE_AMA_PER_TX_EXEC)
+    }
+
+    pub fn storage_limit(&self) -> i128 {
+        self.storage_limit.unwrap_or(crate::consensus::bic::protocol::RESERVE_AMA_PER_TX_STORAGE)
+    }
+}
+
+//sponsor_signature is the sponsor signing the same hash under DST_TX_SPONSOR
 #[derive(Debug, Clone)]
 pub struct TXU {
     pub hash: Vec<u8>,
     pub signature: Vec<u8>,
     pub tx: TX,
+    pub sponsor_signature: Option<Vec<u8>>,
 }
 
 impl EncodeToTerm for Action {
@@ -83,11 +106,28 @@ impl DecodeFromTerm for Action {
 
 impl EncodeToTerm for TX {
     fn to_term(&self) -> Result<Term, &'static str> {
-        Ok(Term::PropList(vec![
+        let action = match self.actions.as_slice() {
+            [action] => (Term::Binary(b"action".to_vec()), action.to_term()?),
+            actions => {
+                let actions: Result<Vec<Term>, &'static str> = actions.iter().map(|a| a.to_term()).collect();
+                (Term::Binary(b"actions".to_vec()), Term::List(actions?))
+            }
+        };
+        let mut pairs = vec![
             (Term::Binary(b"signer".to_vec()), Term::Binary(self.signer.clone())),
             (Term::Binary(b"nonce".to_vec()),  Term::VarInt(self.nonce as i128)),
-            (Term::Binary(b"action".to_vec()), self.action.to_term()?),
-        ]))
+            action,
+        ];
+        if let Some(ref sponsor) = self.sponsor {
+            pairs.push((Term::Binary(b"sponsor".to_vec()), Term::Binary(sponsor.clone())));
+        }
+        if let Some(exec_limit) = self.exec_limit {
+            pairs.push((Term::Binary(b"exec_limit".to_vec()), Term::VarInt(exec_limit)));
+        }
+        if let Some(storage_limit) = self.storage_limit {
+            pairs.push((Term::Binary(b"storage_limit".to_vec()), Term::VarInt(storage_limit)));
+        }
+        Ok(Term::PropList(pairs))
     }
 }
 
@@ -98,10 +138,18 @@ impl DecodeFromTerm for TX {
         let signer = codec::pl_get_bytes(pairs, b"signer").to_vec();
         let nonce  = codec::pl_get_varint(pairs, b"nonce") as u64;
 
-        let action_term = codec::pl_find(pairs, b"action");
-        let action = Action::from_term(action_term);
+        //mirrors to_term so decoding and re-encoding gives the same bytes
+        let actions = match (codec::pl_find_opt(pairs, b"action"), codec::pl_find_opt(pairs, b"actions")) {
+            (None, Some(Term::List(list))) if list.len() >= 2 => list.iter().map(Action::from_term).collect(),
+            (Some(action), None) => vec![Action::from_term(action)],
+            _ => unreachable!("Expected either action or an actions list of at least two"),
+        };
+
+        let sponsor = codec::pl_get_bytes_opt(pairs, b"sponsor").map(|b| b.to_vec());
+        let exec_limit = match codec::pl_find_opt(pairs, b"exec_limit") { Some(Term::VarInt(v)) => Some(*v), _ => None };
+        let storage_limit = match codec::pl_find_opt(pairs, b"storage_limit") { Some(Term::VarInt(v)) => Some(*v), _ => None };
 
-        TX { signer, nonce, action }
+        TX { signer, nonce, actions, sponsor, exec_limit, storage_limit }
     }
 }
 
@@ -112,11 +160,15 @@ pub fn to_bytes_tx(tx: &TX) -> Result<Vec<u8>, &'static str> {
 
 impl EncodeToTerm for TXU {
     fn to_term(&self) -> Result<Term, &'static str> {
-        Ok(Term::PropList(vec![
+        let mut pairs = vec![
             (Term::Binary(b"hash".to_vec()),      Term::Binary(self.hash.clone())),
             (Term::Binary(b"signature".to_vec()), Term::Binary(self.signature.clone())),
             (Term::Binary(b"tx".to_vec()),        self.tx.to_term()?),
-        ]))
+        ];
+        if let Some(ref sponsor_signature) = self.sponsor_signature {
+            pairs.push((Term::Binary(b"sponsor_signature".to_vec()), Term::Binary(sponsor_signature.clone())));
+        }
+        Ok(Term::PropList(pairs))
     }
 }
 
@@ -130,7 +182,9 @@ impl DecodeFromTerm for TXU {
         let tx_term = codec::pl_find(pairs, b"tx");
         let tx = TX::from_term(tx_term);
 
-        TXU { hash, signature, tx }
+        let sponsor_signature = codec::pl_get_bytes_opt(pairs, b"sponsor_signature").map(|b| b.to_vec());
+
+        TXU { hash, signature, tx, sponsor_signature }
     }
 }
 
@@ -138,3 +192,131 @@ pub fn from_bytes(data: &[u8]) -> Result<TXU, &'static str> {
     let term = vecpak::decode(data)?;
     Ok(TXU::from_term(&term))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(function: &[u8]) -> Action {
+        Action {
+            op: b"call".to_vec(), contract: b"Coin".to_vec(), function: function.to_vec(),
+            args: vec![b"a".to_vec(), vec![0u8, 255]], attached_symbol: None, attached_amount: None,
+        }
+    }
+
+    fn tx(actions: Vec<Action>) -> TX {
+        TX { signer: vec![1u8; 48], nonce: 42, actions, sponsor: None, exec_limit: None, storage_limit: None }
+    }
+
+    //decoding and re-encoding must give back the exact bytes that were signed
+    fn roundtrip(tx: &TX) -> (TX, Vec<u8>) {
+        let bytes = to_bytes_tx(tx).unwrap();
+        let decoded = TX::from_term(&vecpak::decode(&bytes).unwrap());
+        assert_eq!(to_bytes_tx(&decoded).unwrap(), bytes);
+        (decoded, bytes)
+    }
+
+    fn keys(bytes: &[u8]) -> Vec<Vec<u8>> {
+        let Ok(Term::PropList(pairs)) = vecpak::decode(bytes) else { panic!("not a proplist") };
+        let mut keys: Vec<Vec<u8>> = pairs.into_iter().map(|(k, _)| match k { Term::Binary(k) => k, _ => panic!("non binary key") }).collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn single_action_encodes_as_action() {
+        let mut a = action(b"transfer");
+        a.attached_symbol = Some(b"AMA".to_vec());
+        a.attached_amount = Some(b"10".to_vec());
+        let (decoded, bytes) = roundtrip(&tx(vec![a]));
+        assert_eq!(keys(&bytes), vec![b"action".to_vec(), b"nonce".to_vec(), b"signer".to_vec()]);
+        assert_eq!(decoded.nonce, 42);
+        assert_eq!(decoded.actions.len(), 1);
+        assert_eq!(decoded.actions[0].args, vec![b"a".to_vec(), vec![0u8, 255]]);
+        assert_eq!(decoded.actions[0].attached_symbol.as_deref(), Some(&b"AMA"[..]));
+        assert_eq!(decoded.actions[0].attached_amount.as_deref(), Some(&b"10"[..]));
+    }
+
+    #[test]
+    fn several_actions_encode_as_actions_in_order() {
+        let (decoded, bytes) = roundtrip(&tx(vec![action(b"first"), action(b"second"), action(b"third")]));
+        assert_eq!(keys(&bytes), vec![b"actions".to_vec(), b"nonce".to_vec(), b"signer".to_vec()]);
+        let functions: Vec<&[u8]> = decoded.actions.iter().map(|a| a.function.as_slice()).collect();
+        assert_eq!(functions, vec![&b"first"[..], &b"second"[..], &b"third"[..]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn single_element_actions_list_is_rejected() {
+        let term = Term::PropList(vec![
+            (Term::Binary(b"signer".to_vec()), Term::Binary(vec![1u8; 48])),
+            (Term::Binary(b"nonce".to_vec()), Term::VarInt(1)),
+            (Term::Binary(b"actions".to_vec()), Term::List(vec![action(b"transfer").to_term().unwrap()])),
+        ]);
+        TX::from_term(&term);
+    }
+
+    #[test]
+    #[should_panic]
+    fn action_and_actions_together_are_rejected() {
+        let term = Term::PropList(vec![
+            (Term::Binary(b"signer".to_vec()), Term::Binary(vec![1u8; 48])),
+            (Term::Binary(b"nonce".to_vec()), Term::VarInt(1)),
+            (Term::Binary(b"action".to_vec()), action(b"transfer").to_term().unwrap()),
+            (Term::Binary(b"actions".to_vec()), Term::List(vec![action(b"a").to_term().unwrap(), action(b"b").to_term().unwrap()])),
+        ]);
+        TX::from_term(&term);
+    }
+
+    #[test]
+    fn sponsor_roundtrip_and_payer() {
+        let plain = tx(vec![action(b"transfer")]);
+        assert_eq!(plain.payer(), &[1u8; 48][..]);
+
+        let mut sponsored = plain.clone();
+        sponsored.sponsor = Some(vec![2u8; 48]);
+        let (decoded, bytes) = roundtrip(&sponsored);
+        assert!(keys(&bytes).contains(&b"sponsor".to_vec()));
+        assert_eq!(decoded.sponsor.as_deref(), Some(&[2u8; 48][..]));
+        assert_eq!(decoded.payer(), &[2u8; 48][..]);
+
+        let txu = TXU { hash: vec![3u8; 32], signature: vec![4u8; 96], tx: sponsored, sponsor_signature: Some(vec![5u8; 96]) };
+        let bytes = vecpak::encode(txu.to_term().unwrap());
+        let decoded = from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.sponsor_signature.as_deref(), Some(&[5u8; 96][..]));
+        assert_eq!(vecpak::encode(decoded.to_term().unwrap()), bytes);
+    }
+
+    #[test]
+    fn no_sponsor_leaves_encoding_unchanged() {
+        let (decoded, bytes) = roundtrip(&tx(vec![action(b"transfer")]));
+        assert!(decoded.sponsor.is_none());
+        assert!(!keys(&bytes).contains(&b"sponsor".to_vec()));
+    }
+
+    #[test]
+    fn limits_roundtrip_and_defaults() {
+        use crate::consensus::bic::protocol;
+
+        let plain = tx(vec![action(b"transfer")]);
+        assert_eq!(plain.exec_limit(), protocol::RESERVE_AMA_PER_TX_EXEC);
+        assert_eq!(plain.storage_limit(), protocol::RESERVE_AMA_PER_TX_STORAGE);
+
+        let mut limited = plain.clone();
+        limited.exec_limit = Some(protocol::TX_MIN_EXEC_LIMIT);
+        limited.storage_limit = Some(0);
+        let (decoded, bytes) = roundtrip(&limited);
+        assert!(keys(&bytes).contains(&b"exec_limit".to_vec()));
+        assert!(keys(&bytes).contains(&b"storage_limit".to_vec()));
+        assert_eq!(decoded.exec_limit, Some(protocol::TX_MIN_EXEC_LIMIT));
+        //an explicit zero survives the round trip instead of falling back to the reserve
+        assert_eq!(decoded.storage_limit, Some(0));
+        assert_eq!(decoded.storage_limit(), 0);
+
+        let mut exec_only = plain.clone();
+        exec_only.exec_limit = Some(protocol::TX_MAX_EXEC_LIMIT);
+        let (decoded, bytes) = roundtrip(&exec_only);
+        assert!(!keys(&bytes).contains(&b"storage_limit".to_vec()));
+        assert_eq!(decoded.storage_limit(), protocol::RESERVE_AMA_PER_TX_STORAGE);
+    }
+}
@@ -9,11 +9,46 @@ pub struct TXReceipt {
     pub result: Vec<u8>,
     pub exec_used: Vec<u8>,
     pub logs: Vec<Vec<u8>>,
+    pub events: Vec<TXEvent>,
+    //per action results, only for multi action txs
+    pub results: Vec<Vec<u8>>,
 }
 
-impl EncodeToTerm for TXReceipt {
+#[derive(Debug, Clone)]
+pub struct TXEvent {
+    pub contract: Vec<u8>,
+    pub topics: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+}
+
+impl EncodeToTerm for TXEvent {
     fn to_term(&self) -> Result<Term, &'static str> {
         Ok(Term::PropList(vec![
+            (Term::Binary(b"contract".to_vec()), Term::Binary(self.contract.to_vec())),
+            (
+                Term::Binary(b"topics".to_vec()),
+                Term::List(self.topics.iter().map(|topic| Term::Binary(topic.clone())).collect())
+            ),
+            (Term::Binary(b"data".to_vec()), Term::Binary(self.data.to_vec())),
+        ]))
+    }
+}
+
+impl DecodeFromTerm for TXEvent {
+    fn from_term(t: &Term) -> Self {
+        let Term::PropList(pairs) = t else { unreachable!() };
+
+        let contract  = codec::pl_get_bytes(pairs,   b"contract").to_vec();
+        let topics  = codec::pl_get_list_of_bytes(pairs,   b"topics");
+        let data  = codec::pl_get_bytes(pairs,   b"data").to_vec();
+
+        TXEvent { contract, topics, data }
+    }
+}
+
+impl EncodeToTerm for TXReceipt {
+    fn to_term(&self) -> Result<Term, &'static str> {
+        let mut pairs = vec![
             (Term::Binary(b"txid".to_vec()), Term::Binary(self.txid.to_vec())),
             (Term::Binary(b"success".to_vec()),  Term::Bool(self.success)),
             (Term::Binary(b"result".to_vec()),  Term::Binary(self.result.to_vec())),
@@ -22,7 +57,16 @@ impl EncodeToTerm for TXReceipt {
                 Term::Binary(b"logs".to_vec()),
                 Term::List(self.logs.iter().map(|log| Term::Binary(log.clone())).collect())
             ),
-        ]))
+        ];
+        //only present when a contract emitted events, keeps older receipts byte identical
+        if !self.events.is_empty() {
+            let events: Result<Vec<Term>, &'static str> = self.events.iter().map(|event| event.to_term()).collect();
+            pairs.push((Term::Binary(b"events".to_vec()), Term::List(events?)));
+        }
+        if !self.results.is_empty() {
+            pairs.push((Term::Binary(b"results".to_vec()), Term::List(self.results.iter().map(|r| Term::Binary(r.clone())).collect())));
+        }
+        Ok(Term::PropList(pairs))
     }
 }
 
@@ -35,7 +79,16 @@ impl DecodeFromTerm for TXReceipt {
         let result  = codec::pl_get_bytes(pairs,   b"result").to_vec();
         let exec_used  = codec::pl_get_bytes(pairs,   b"exec_used").to_vec();
         let logs  = codec::pl_get_list_of_bytes(pairs,   b"logs");
+        let events = match codec::pl_find_opt(pairs, b"events") {
+            Some(Term::List(list)) => list.iter().map(TXEvent::from_term).collect(),
+            _ => Vec::new(),
+        };
+
+        let results = match codec::pl_find_opt(pairs, b"results") {
+            Some(Term::List(_)) => codec::pl_get_list_of_bytes(pairs, b"results"),
+            _ => Vec::new(),
+        };
 
-        TXReceipt { txid, success, result, exec_used, logs }
+        TXReceipt { txid, success, result, exec_used, logs, events, results }
     }
 }
@@ -10,6 +10,7 @@ rustler::atoms! {
     signer,
     tx,
     action,
+    actions,
     contract,
     function,
     args,
@@ -53,55 +54,62 @@ pub fn build_tx_hashfilters<'a>(env: Env<'a>, txus: Vec<Term<'a>>) -> NifResult<
         let signer_bin: Binary = tx.map_get(signer())?.decode()?;
         let signer = signer_bin.as_slice();
 
-        let action_map = tx.map_get(action())?;
-        let contract_bin: Binary = action_map.map_get(contract())?.decode()?;
-        let contract = contract_bin.as_slice();
-        let function_bin: Binary = action_map.map_get(function())?.decode()?;
-        let func = function_bin.as_slice();
-
-        let args_list: Vec<Term> = action_map.map_get(args())?.decode()?;
-        let arg0 = if let Some(first_arg) = args_list.first() {
-            let b: Binary = first_arg.decode()?;
-            b.as_slice()
-        } else {
-            ZERO
+        //multi action txs are indexed under every action
+        let action_maps: Vec<Term> = match tx.map_get(actions()) {
+            Ok(list) => list.decode()?,
+            Err(_) => vec![tx.map_get(action())?],
         };
 
-        let mut push_key = |parts: &[&[u8]]| {
-            let raw_hash = create_filter_key(parts);
-
-            let mut bin = NewBinary::new(env, 24);
-            let s = bin.as_mut_slice();
-
-            s[0..16].copy_from_slice(&raw_hash);
-            s[16..24].copy_from_slice(&nonce_bytes);
-
-            all_filters.push((bin.into(), tx_hash8));
-        };
-
-        match (contract, func) {
-            (b"Epoch", b"submit_sol") => {
-                push_key(&[signer, ZERO, ZERO, ZERO]);
-                push_key(&[ZERO, arg0, ZERO, ZERO]);
-                // Do we care about this?
-                // tx_filter CF goes from 10G to 22G if we add this back
-                // reevaluate later difference is OK
-                push_key(&[signer, ZERO, contract, ZERO]);
-                push_key(&[signer, ZERO, contract, func]);
-                push_key(&[ZERO, ZERO, contract, ZERO]);
-                push_key(&[ZERO, ZERO, contract, func]);
-            },
-            _ => {
-                push_key(&[signer, ZERO, ZERO, ZERO]);
-                push_key(&[ZERO, arg0, ZERO, ZERO]);
-                push_key(&[signer, arg0, ZERO, ZERO]);
-                push_key(&[ZERO, ZERO, contract, ZERO]);
-                push_key(&[ZERO, ZERO, contract, func]);
-                push_key(&[signer, ZERO, contract, ZERO]);
-                push_key(&[signer, ZERO, contract, func]);
-                push_key(&[ZERO, arg0, contract, ZERO]);
-                push_key(&[ZERO, arg0, contract, func]);
-                push_key(&[signer, arg0, contract, func]);
+        for action_map in action_maps {
+            let contract_bin: Binary = action_map.map_get(contract())?.decode()?;
+            let contract = contract_bin.as_slice();
+            let function_bin: Binary = action_map.map_get(function())?.decode()?;
+            let func = function_bin.as_slice();
+
+            let args_list: Vec<Term> = action_map.map_get(args())?.decode()?;
+            let arg0 = if let Some(first_arg) = args_list.first() {
+                let b: Binary = first_arg.decode()?;
+                b.as_slice()
+            } else {
+                ZERO
+            };
+
+            let mut push_key = |parts: &[&[u8]]| {
+                let raw_hash = create_filter_key(parts);
+
+                let mut bin = NewBinary::new(env, 24);
+                let s = bin.as_mut_slice();
+
+                s[0..16].copy_from_slice(&raw_hash);
+                s[16..24].copy_from_slice(&nonce_bytes);
+
+                all_filters.push((bin.into(), tx_hash8));
+            };
+
+            match (contract, func) {
+                (b"Epoch", b"submit_sol") => {
+                    push_key(&[signer, ZERO, ZERO, ZERO]);
+                    push_key(&[ZERO, arg0, ZERO, ZERO]);
+                    // Do we care about this?
+                    // tx_filter CF goes from 10G to 22G if we add this back
+                    // reevaluate later difference is OK
+                    push_key(&[signer, ZERO, contract, ZERO]);
+                    push_key(&[signer, ZERO, contract, func]);
+                    push_key(&[ZERO, ZERO, contract, ZERO]);
+                    push_key(&[ZERO, ZERO, contract, func]);
+                },
+                _ => {
+                    push_key(&[signer, ZERO, ZERO, ZERO]);
+                    push_key(&[ZERO, arg0, ZERO, ZERO]);
+                    push_key(&[signer, arg0, ZERO, ZERO]);
+                    push_key(&[ZERO, ZERO, contract, ZERO]);
+                    push_key(&[ZERO, ZERO, contract, func]);
+                    push_key(&[signer, ZERO, contract, ZERO]);
+                    push_key(&[signer, ZERO, contract, func]);
+                    push_key(&[ZERO, arg0, contract, ZERO]);
+                    push_key(&[ZERO, arg0, contract, func]);
+                    push_key(&[signer, arg0, contract, func]);
+                }
             }
         }
     }